@@ -0,0 +1,609 @@
+//! AI 客户端子系统
+//!
+//! 统一封装各家供应商的请求/响应格式，让 `chat_completion` 等命令可以直接
+//! 对着 `dyn AiClient` 编程，而不必在每个调用点重复 `test_provider_connection`
+//! 里那套按 `AIProviderType` 分支的 URL/鉴权拼接逻辑。
+
+use crate::config::{AIProviderConfig, AIProviderType};
+use crate::utf8_buffer::Utf8ChunkBuffer;
+use async_trait::async_trait;
+use futures::stream::{self, BoxStream, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// 一条对话消息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatMessage {
+    pub role: String,
+    pub content: String,
+}
+
+/// 流式响应中的一个增量片段
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Chunk {
+    pub delta: String,
+    pub done: bool,
+}
+
+/// 所有 AI 客户端需要实现的能力
+#[async_trait]
+pub trait AiClient: Send + Sync {
+    /// 发送一次完整对话，返回整段回复
+    async fn chat(&self, messages: &[ChatMessage], model: &str) -> Result<String, String>;
+
+    /// 以流式方式发送对话，逐段返回增量内容
+    async fn chat_stream(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+    ) -> Result<BoxStream<'static, Result<Chunk, String>>, String>;
+}
+
+/// 生成 `init` 构造函数，按供应商类型分发到对应的客户端实现。
+///
+/// 用法：`register_client!((OpenAI, OpenAiClient), ...)`，每一项对应一个
+/// 供应商：`AIProviderType` 枚举 variant、客户端实现类型。两者是不同的
+/// 标识符（`AIProviderType::OpenAI` vs. `OpenAiClient`），不能共用一个 token。
+macro_rules! register_client {
+    ($(($variant:ident, $client:ident)),* $(,)?) => {
+        /// 根据存储的 `AIProviderConfig` 解析出对应的客户端实现
+        pub fn init(provider: &AIProviderConfig) -> Box<dyn AiClient> {
+            match provider.provider_type {
+                $(AIProviderType::$variant => Box::new($client::from_provider(provider)),)*
+            }
+        }
+    };
+}
+
+/// OpenAI 客户端配置（目前只需要基础字段，预留给未来的每供应商专属选项）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenAiConfig {
+    pub base_url: String,
+    pub api_key: String,
+}
+
+/// Anthropic 客户端配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnthropicConfig {
+    pub base_url: String,
+    pub api_key: String,
+}
+
+/// Ollama 客户端配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OllamaConfig {
+    pub base_url: String,
+}
+
+/// Azure OpenAI 客户端配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AzureConfig {
+    pub endpoint: String,
+    pub deployment: String,
+    pub api_version: String,
+    pub api_key: String,
+}
+
+/// 自定义 OpenAI 兼容端点配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomConfig {
+    pub base_url: String,
+    pub api_key: String,
+}
+
+register_client!(
+    (OpenAI, OpenAiClient),
+    (Anthropic, AnthropicClient),
+    (Ollama, OllamaClient),
+    (Azure, AzureClient),
+    (Custom, CustomClient),
+);
+
+pub struct OpenAiClient {
+    config: OpenAiConfig,
+}
+
+impl OpenAiClient {
+    fn from_provider(provider: &AIProviderConfig) -> Self {
+        Self {
+            config: OpenAiConfig {
+                base_url: provider
+                    .base_url
+                    .clone()
+                    .unwrap_or_else(|| "https://api.openai.com/v1".to_string()),
+                api_key: provider.api_key.clone(),
+            },
+        }
+    }
+}
+
+#[async_trait]
+impl AiClient for OpenAiClient {
+    async fn chat(&self, messages: &[ChatMessage], model: &str) -> Result<String, String> {
+        let client = reqwest::Client::new();
+        let body = serde_json::json!({
+            "model": model,
+            "messages": messages,
+        });
+
+        let response = client
+            .post(format!("{}/chat/completions", self.config.base_url))
+            .header("Authorization", format!("Bearer {}", self.config.api_key))
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| format!("请求失败: {}", e))?;
+
+        if !response.status().is_success() {
+            return Err(format!("HTTP {}", response.status()));
+        }
+
+        let json: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|e| format!("解析响应失败: {}", e))?;
+
+        json.get("choices")
+            .and_then(|c| c.get(0))
+            .and_then(|c| c.get("message"))
+            .and_then(|m| m.get("content"))
+            .and_then(|c| c.as_str())
+            .map(|s| s.to_string())
+            .ok_or_else(|| "响应中缺少 choices[0].message.content".to_string())
+    }
+
+    async fn chat_stream(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+    ) -> Result<BoxStream<'static, Result<Chunk, String>>, String> {
+        let client = reqwest::Client::new();
+        let body = serde_json::json!({
+            "model": model,
+            "messages": messages,
+            "stream": true,
+        });
+
+        let response = client
+            .post(format!("{}/chat/completions", self.config.base_url))
+            .header("Authorization", format!("Bearer {}", self.config.api_key))
+            .header("Accept", "text/event-stream")
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| format!("请求失败: {}", e))?;
+
+        if !response.status().is_success() {
+            return Err(format!("HTTP {}", response.status()));
+        }
+
+        Ok(sse_payload_stream(response)
+            .map(|payload| {
+                let data = payload?;
+                if data == "[DONE]" {
+                    return Ok(Chunk { delta: String::new(), done: true });
+                }
+                let json: serde_json::Value =
+                    serde_json::from_str(&data).map_err(|e| format!("解析流事件失败: {}", e))?;
+                let delta = json
+                    .get("choices")
+                    .and_then(|c| c.get(0))
+                    .and_then(|c| c.get("delta"))
+                    .and_then(|d| d.get("content"))
+                    .and_then(|c| c.as_str())
+                    .unwrap_or_default()
+                    .to_string();
+                Ok(Chunk { delta, done: false })
+            })
+            .boxed())
+    }
+}
+
+pub struct AnthropicClient {
+    config: AnthropicConfig,
+}
+
+impl AnthropicClient {
+    fn from_provider(provider: &AIProviderConfig) -> Self {
+        Self {
+            config: AnthropicConfig {
+                base_url: provider
+                    .base_url
+                    .clone()
+                    .unwrap_or_else(|| "https://api.anthropic.com".to_string()),
+                api_key: provider.api_key.clone(),
+            },
+        }
+    }
+}
+
+#[async_trait]
+impl AiClient for AnthropicClient {
+    async fn chat(&self, messages: &[ChatMessage], model: &str) -> Result<String, String> {
+        let client = reqwest::Client::new();
+        let body = serde_json::json!({
+            "model": model,
+            "max_tokens": 4096,
+            "messages": messages,
+        });
+
+        let response = client
+            .post(format!("{}/v1/messages", self.config.base_url))
+            .header("x-api-key", &self.config.api_key)
+            .header("anthropic-version", "2023-06-01")
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| format!("请求失败: {}", e))?;
+
+        if !response.status().is_success() {
+            return Err(format!("HTTP {}", response.status()));
+        }
+
+        let json: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|e| format!("解析响应失败: {}", e))?;
+
+        json.get("content")
+            .and_then(|c| c.get(0))
+            .and_then(|c| c.get("text"))
+            .and_then(|t| t.as_str())
+            .map(|s| s.to_string())
+            .ok_or_else(|| "响应中缺少 content[0].text".to_string())
+    }
+
+    async fn chat_stream(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+    ) -> Result<BoxStream<'static, Result<Chunk, String>>, String> {
+        let client = reqwest::Client::new();
+        let body = serde_json::json!({
+            "model": model,
+            "max_tokens": 4096,
+            "messages": messages,
+            "stream": true,
+        });
+
+        let response = client
+            .post(format!("{}/v1/messages", self.config.base_url))
+            .header("x-api-key", &self.config.api_key)
+            .header("anthropic-version", "2023-06-01")
+            .header("Accept", "text/event-stream")
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| format!("请求失败: {}", e))?;
+
+        if !response.status().is_success() {
+            return Err(format!("HTTP {}", response.status()));
+        }
+
+        // Anthropic 的 SSE 事件里除了 content_block_delta 还会出现
+        // message_start/ping/content_block_stop 等，逐条过滤成只保留文本增量
+        Ok(sse_payload_stream(response)
+            .filter_map(|payload| async move {
+                let data = match payload {
+                    Ok(data) => data,
+                    Err(e) => return Some(Err(e)),
+                };
+                let json: serde_json::Value = match serde_json::from_str(&data) {
+                    Ok(json) => json,
+                    Err(e) => return Some(Err(format!("解析流事件失败: {}", e))),
+                };
+                match json.get("type").and_then(|t| t.as_str()) {
+                    Some("content_block_delta") => {
+                        let text = json
+                            .get("delta")
+                            .and_then(|d| d.get("text"))
+                            .and_then(|t| t.as_str())
+                            .unwrap_or_default()
+                            .to_string();
+                        Some(Ok(Chunk { delta: text, done: false }))
+                    }
+                    Some("message_stop") => Some(Ok(Chunk { delta: String::new(), done: true })),
+                    _ => None,
+                }
+            })
+            .boxed())
+    }
+}
+
+pub struct OllamaClient {
+    config: OllamaConfig,
+}
+
+impl OllamaClient {
+    fn from_provider(provider: &AIProviderConfig) -> Self {
+        Self {
+            config: OllamaConfig {
+                base_url: provider
+                    .base_url
+                    .clone()
+                    .unwrap_or_else(|| "http://localhost:11434".to_string()),
+            },
+        }
+    }
+}
+
+#[async_trait]
+impl AiClient for OllamaClient {
+    async fn chat(&self, messages: &[ChatMessage], model: &str) -> Result<String, String> {
+        let client = reqwest::Client::new();
+        let body = serde_json::json!({
+            "model": model,
+            "messages": messages,
+            "stream": false,
+        });
+
+        let response = client
+            .post(format!("{}/api/chat", self.config.base_url))
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| format!("请求失败: {}", e))?;
+
+        if !response.status().is_success() {
+            return Err(format!("HTTP {}", response.status()));
+        }
+
+        let json: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|e| format!("解析响应失败: {}", e))?;
+
+        json.get("message")
+            .and_then(|m| m.get("content"))
+            .and_then(|c| c.as_str())
+            .map(|s| s.to_string())
+            .ok_or_else(|| "响应中缺少 message.content".to_string())
+    }
+
+    async fn chat_stream(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+    ) -> Result<BoxStream<'static, Result<Chunk, String>>, String> {
+        let client = reqwest::Client::new();
+        let body = serde_json::json!({
+            "model": model,
+            "messages": messages,
+            "stream": true,
+        });
+
+        let response = client
+            .post(format!("{}/api/chat", self.config.base_url))
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| format!("请求失败: {}", e))?;
+
+        if !response.status().is_success() {
+            return Err(format!("HTTP {}", response.status()));
+        }
+
+        // Ollama 不是 SSE，而是逐行 NDJSON，每行一个完整 JSON 对象
+        Ok(ndjson_stream(response)
+            .map(|line| {
+                let line = line?;
+                let json: serde_json::Value =
+                    serde_json::from_str(&line).map_err(|e| format!("解析流事件失败: {}", e))?;
+                let delta = json
+                    .get("message")
+                    .and_then(|m| m.get("content"))
+                    .and_then(|c| c.as_str())
+                    .unwrap_or_default()
+                    .to_string();
+                let done = json.get("done").and_then(|d| d.as_bool()).unwrap_or(false);
+                Ok(Chunk { delta, done })
+            })
+            .boxed())
+    }
+}
+
+pub struct AzureClient {
+    config: AzureConfig,
+}
+
+impl AzureClient {
+    fn from_provider(provider: &AIProviderConfig) -> Self {
+        Self {
+            config: AzureConfig {
+                endpoint: provider.azure_endpoint.clone().unwrap_or_default(),
+                deployment: provider.azure_deployment.clone().unwrap_or_default(),
+                api_version: provider
+                    .azure_api_version
+                    .clone()
+                    .unwrap_or_else(|| "2024-02-15-preview".to_string()),
+                api_key: provider.api_key.clone(),
+            },
+        }
+    }
+}
+
+#[async_trait]
+impl AiClient for AzureClient {
+    async fn chat(&self, messages: &[ChatMessage], _model: &str) -> Result<String, String> {
+        let client = reqwest::Client::new();
+        let body = serde_json::json!({
+            "messages": messages,
+        });
+
+        let url = format!(
+            "{}/openai/deployments/{}/chat/completions?api-version={}",
+            self.config.endpoint, self.config.deployment, self.config.api_version
+        );
+
+        let response = client
+            .post(&url)
+            .header("api-key", &self.config.api_key)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| format!("请求失败: {}", e))?;
+
+        if !response.status().is_success() {
+            return Err(format!("HTTP {}", response.status()));
+        }
+
+        let json: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|e| format!("解析响应失败: {}", e))?;
+
+        json.get("choices")
+            .and_then(|c| c.get(0))
+            .and_then(|c| c.get("message"))
+            .and_then(|m| m.get("content"))
+            .and_then(|c| c.as_str())
+            .map(|s| s.to_string())
+            .ok_or_else(|| "响应中缺少 choices[0].message.content".to_string())
+    }
+
+    async fn chat_stream(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+    ) -> Result<BoxStream<'static, Result<Chunk, String>>, String> {
+        let text = self.chat(messages, model).await?;
+        Ok(single_chunk_stream(text))
+    }
+}
+
+pub struct CustomClient {
+    config: CustomConfig,
+}
+
+impl CustomClient {
+    fn from_provider(provider: &AIProviderConfig) -> Self {
+        Self {
+            config: CustomConfig {
+                base_url: provider.base_url.clone().unwrap_or_default(),
+                api_key: provider.api_key.clone(),
+            },
+        }
+    }
+}
+
+#[async_trait]
+impl AiClient for CustomClient {
+    async fn chat(&self, messages: &[ChatMessage], model: &str) -> Result<String, String> {
+        let client = reqwest::Client::new();
+        let body = serde_json::json!({
+            "model": model,
+            "messages": messages,
+        });
+
+        let response = client
+            .post(format!("{}/chat/completions", self.config.base_url))
+            .header("Authorization", format!("Bearer {}", self.config.api_key))
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| format!("请求失败: {}", e))?;
+
+        if !response.status().is_success() {
+            return Err(format!("HTTP {}", response.status()));
+        }
+
+        let json: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|e| format!("解析响应失败: {}", e))?;
+
+        json.get("choices")
+            .and_then(|c| c.get(0))
+            .and_then(|c| c.get("message"))
+            .and_then(|m| m.get("content"))
+            .and_then(|c| c.as_str())
+            .map(|s| s.to_string())
+            .ok_or_else(|| "响应中缺少 choices[0].message.content".to_string())
+    }
+
+    async fn chat_stream(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+    ) -> Result<BoxStream<'static, Result<Chunk, String>>, String> {
+        let text = self.chat(messages, model).await?;
+        Ok(single_chunk_stream(text))
+    }
+}
+
+/// 把响应体解析成逐条 `data:` 负载的流，供各家基于 SSE 的供应商共用：
+/// 事件以空行分隔，不完整的尾部留到下次读取时再拼接（与 `stream_model`
+/// 里的解析逻辑一致）。
+fn sse_payload_stream(response: reqwest::Response) -> BoxStream<'static, Result<String, String>> {
+    stream::unfold(
+        (response.bytes_stream(), Utf8ChunkBuffer::default(), String::new(), VecDeque::new()),
+        |(mut byte_stream, mut utf8_buf, mut leftover, mut pending)| async move {
+            loop {
+                if let Some(payload) = pending.pop_front() {
+                    return Some((Ok(payload), (byte_stream, utf8_buf, leftover, pending)));
+                }
+                match byte_stream.next().await {
+                    Some(Ok(bytes)) => {
+                        leftover.push_str(&utf8_buf.push(&bytes));
+                        while let Some(pos) = leftover.find("\n\n") {
+                            let event = leftover[..pos].to_string();
+                            leftover.drain(..pos + 2);
+                            for line in event.lines() {
+                                if let Some(data) = line.strip_prefix("data:") {
+                                    pending.push_back(data.trim().to_string());
+                                }
+                            }
+                        }
+                    }
+                    Some(Err(e)) => return Some((Err(format!("读取流失败: {}", e)), (byte_stream, utf8_buf, leftover, pending))),
+                    None => return None,
+                }
+            }
+        },
+    )
+    .boxed()
+}
+
+/// 把响应体解析成逐行 NDJSON 的流，给 Ollama 这类不走 SSE、而是每行输出
+/// 一个完整 JSON 对象的供应商使用。
+fn ndjson_stream(response: reqwest::Response) -> BoxStream<'static, Result<String, String>> {
+    stream::unfold(
+        (response.bytes_stream(), Utf8ChunkBuffer::default(), String::new(), VecDeque::new()),
+        |(mut byte_stream, mut utf8_buf, mut leftover, mut pending)| async move {
+            loop {
+                if let Some(line) = pending.pop_front() {
+                    return Some((Ok(line), (byte_stream, utf8_buf, leftover, pending)));
+                }
+                match byte_stream.next().await {
+                    Some(Ok(bytes)) => {
+                        leftover.push_str(&utf8_buf.push(&bytes));
+                        while let Some(pos) = leftover.find('\n') {
+                            let line = leftover[..pos].trim().to_string();
+                            leftover.drain(..=pos);
+                            if !line.is_empty() {
+                                pending.push_back(line);
+                            }
+                        }
+                    }
+                    Some(Err(e)) => return Some((Err(format!("读取流失败: {}", e)), (byte_stream, utf8_buf, leftover, pending))),
+                    None => return None,
+                }
+            }
+        },
+    )
+    .boxed()
+}
+
+/// 把一段已经拿到的完整文本包装成单个 chunk 的流，给暂不支持增量输出的
+/// 供应商实现一个可用但非逐 token 的 `chat_stream`。
+fn single_chunk_stream(text: String) -> BoxStream<'static, Result<Chunk, String>> {
+    futures::stream::once(async move {
+        Ok(Chunk {
+            delta: text,
+            done: true,
+        })
+    })
+    .boxed()
+}
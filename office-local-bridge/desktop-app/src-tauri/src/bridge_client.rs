@@ -0,0 +1,108 @@
+//! Bridge 请求助手
+//!
+//! 这里几乎每个命令都在重复同一套模板：建一个 `reqwest::Client`、发
+//! POST/GET、检查 `status().is_success()`、挖出 `json.get("data")`、
+//! `serde_json::from_value`，再拼三种不同的错误字符串。`bridge_request`
+//! 把这套模板收敛成一个泛型助手，统一解开 `{ success, data }` 信封，并在
+//! 连接错误或 5xx 上按指数退避 + 抖动重试（4xx 视为客户端错误，不重试，
+//! 直接失败）。重试次数、基础延迟、超时都从 `BridgeConfig` 读取，方便
+//! 本地 Bridge 不稳定或模型冷启动慢的用户调整，而不必重新编译。
+
+use crate::commands::{ApiResponse, ErrorKind};
+use crate::config::{build_client_with_timeout, get_config_path, read_config, BridgeConfig};
+use reqwest::Method;
+use serde::de::DeserializeOwned;
+use std::time::Duration;
+
+/// 向 Bridge 发起一次请求，自动解开 `{ success, data }` 信封并在可重试的
+/// 失败上退避重试
+pub async fn bridge_request<T: DeserializeOwned>(
+    method: Method,
+    path: &str,
+    body: Option<serde_json::Value>,
+) -> ApiResponse<T> {
+    let config: BridgeConfig = read_config(&get_config_path());
+    let url = format!("http://{}:{}{}", config.host, config.port, path);
+    let proxy = crate::config::resolve_proxy(None, None, &config);
+    let client = build_client_with_timeout(
+        proxy.as_deref(),
+        Duration::from_secs(config.request_timeout_secs),
+    );
+
+    let mut attempt = 0u32;
+
+    loop {
+        let mut request = client.request(method.clone(), &url);
+        if let Some(b) = &body {
+            request = request.json(b);
+        }
+
+        match request.send().await {
+            Ok(response) => {
+                let status = response.status();
+
+                if status.is_success() {
+                    return parse_envelope(response).await;
+                }
+
+                if status.is_server_error() && attempt < config.retry_count {
+                    attempt += 1;
+                    sleep_with_backoff(config.retry_base_delay_ms, attempt).await;
+                    continue;
+                }
+
+                // 4xx 是客户端自己的问题（鉴权、参数等），重试也不会变好，直接失败
+                let error_text = response.text().await.unwrap_or_default();
+                return ApiResponse::error_with_kind(
+                    &format!("请求失败: HTTP {} - {}", status, error_text),
+                    ErrorKind::Upstream { status: status.as_u16() },
+                );
+            }
+            Err(e) => {
+                if attempt < config.retry_count {
+                    attempt += 1;
+                    sleep_with_backoff(config.retry_base_delay_ms, attempt).await;
+                    continue;
+                }
+                let kind = if e.is_timeout() {
+                    ErrorKind::Timeout
+                } else {
+                    ErrorKind::ConnectionFailed
+                };
+                return ApiResponse::error_with_kind(&format!("请求失败: {}", e), kind);
+            }
+        }
+    }
+}
+
+async fn parse_envelope<T: DeserializeOwned>(response: reqwest::Response) -> ApiResponse<T> {
+    match response.json::<serde_json::Value>().await {
+        Ok(json) => {
+            let data = json.get("data").unwrap_or(&json);
+            match serde_json::from_value::<T>(data.clone()) {
+                Ok(value) => ApiResponse::success(value),
+                Err(e) => ApiResponse::error_with_kind(&format!("解析响应失败: {}", e), ErrorKind::ParseError),
+            }
+        }
+        Err(e) => ApiResponse::error_with_kind(&format!("解析响应失败: {}", e), ErrorKind::ParseError),
+    }
+}
+
+/// 指数退避 + 抖动，避免重试请求集中撞在同一时刻
+async fn sleep_with_backoff(base_delay_ms: u64, attempt: u32) {
+    let exp = base_delay_ms.saturating_mul(1u64 << attempt.min(10));
+    let jitter = (SystemTimeNanos::now() % 100) as u64;
+    tokio::time::sleep(Duration::from_millis(exp + jitter)).await;
+}
+
+/// 用系统时间的纳秒位当作轻量抖动源，避免为此引入专门的随机数依赖
+struct SystemTimeNanos;
+
+impl SystemTimeNanos {
+    fn now() -> u128 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.subsec_nanos() as u128)
+            .unwrap_or(0)
+    }
+}
@@ -0,0 +1,246 @@
+//! 桥接服务监督子系统
+//!
+//! `BridgeProcessState` 原先只是一个裸的 `Mutex<Option<Child>>`，只有命令
+//! 被调用时才知道进程是否还活着。这里在其上补上原子化的存活状态，并在应用
+//! 启动时起一个轮询任务：定期 `try_wait()` 子进程、探测 `/health`，一旦发现
+//! 意外退出就按退避策略自动重启，同时把每次状态变化通过 Tauri 事件广播出去。
+
+use crate::commands::{get_bridge_service_path, get_bridge_start_command};
+use crate::config::{build_client_with_timeout, get_config_path, read_config, BridgeConfig};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager};
+
+/// 桥接服务进程状态：子进程句柄 + 监督所需的原子状态
+pub struct BridgeProcessState {
+    pub child: Mutex<Option<Child>>,
+    /// 当前是否“应当”运行 —— 用户主动停止时置为 false，监督循环据此区分
+    /// “正常停止”和“意外崩溃”
+    pub running: AtomicBool,
+    pub restart_count: AtomicU32,
+    pub started_at: AtomicI64,
+    /// 最近一次崩溃或重启失败的原因，供 `status()` 命令展示给用户
+    pub last_error: Mutex<Option<String>>,
+}
+
+impl Default for BridgeProcessState {
+    fn default() -> Self {
+        Self {
+            child: Mutex::new(None),
+            running: AtomicBool::new(false),
+            restart_count: AtomicU32::new(0),
+            started_at: AtomicI64::new(0),
+            last_error: Mutex::new(None),
+        }
+    }
+}
+
+/// 监督循环推送的状态事件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BridgeSupervisorEvent {
+    pub running: bool,
+    pub restart_count: u32,
+    pub started_at: i64,
+    pub reason: String,
+    pub last_error: Option<String>,
+}
+
+fn now_ts() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn emit_event(app_handle: &AppHandle, state: &BridgeProcessState, reason: &str) {
+    let event = BridgeSupervisorEvent {
+        running: state.running.load(Ordering::SeqCst),
+        restart_count: state.restart_count.load(Ordering::SeqCst),
+        started_at: state.started_at.load(Ordering::SeqCst),
+        reason: reason.to_string(),
+        last_error: state.last_error.lock().ok().and_then(|g| g.clone()),
+    };
+    let _ = app_handle.emit("office://bridge-supervisor", &event);
+}
+
+/// 启动命令成功 spawn 子进程后调用，标记为“期望运行”
+pub fn mark_started(state: &BridgeProcessState, app_handle: &AppHandle) {
+    state.running.store(true, Ordering::SeqCst);
+    state.started_at.store(now_ts(), Ordering::SeqCst);
+    state.restart_count.store(0, Ordering::SeqCst);
+    if let Ok(mut guard) = state.last_error.lock() {
+        *guard = None;
+    }
+    emit_event(app_handle, state, "started");
+}
+
+/// 记录一次失败原因，供 `status()` 命令展示
+fn mark_error(state: &BridgeProcessState, error: impl Into<String>) {
+    if let Ok(mut guard) = state.last_error.lock() {
+        *guard = Some(error.into());
+    }
+}
+
+/// 用户主动发起停止时调用，避免监督循环把这次退出当成崩溃
+pub fn mark_stopping(state: &BridgeProcessState) {
+    state.running.store(false, Ordering::SeqCst);
+}
+
+/// 停止命令完成后调用
+pub fn mark_stopped(state: &BridgeProcessState, app_handle: &AppHandle) {
+    state.started_at.store(0, Ordering::SeqCst);
+    emit_event(app_handle, state, "stopped");
+}
+
+/// 按现有的 `get_bridge_start_command` 逻辑拉起一个新的桥接服务子进程
+pub fn spawn_bridge_child(app_handle: &AppHandle) -> Result<Child, String> {
+    let service_path = get_bridge_service_path(app_handle);
+    let (cmd, args, cwd) = get_bridge_start_command(&service_path);
+
+    let mut child = Command::new(&cmd)
+        .args(&args)
+        .current_dir(&cwd)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("启动服务失败: {}", e))?;
+
+    drain_pipe(child.stdout.take(), app_handle.clone(), "stdout");
+    drain_pipe(child.stderr.take(), app_handle.clone(), "stderr");
+
+    Ok(child)
+}
+
+/// 把子进程的 stdout/stderr 逐行读出并转发为 Tauri 事件，避免崩溃时的输出
+/// 只留在已经消失的管道里
+fn drain_pipe<R: std::io::Read + Send + 'static>(pipe: Option<R>, app_handle: AppHandle, stream: &'static str) {
+    let Some(pipe) = pipe else { return };
+    std::thread::spawn(move || {
+        let reader = BufReader::new(pipe);
+        for line in reader.lines().map_while(Result::ok) {
+            let _ = app_handle.emit("office://bridge-process-log", serde_json::json!({
+                "stream": stream,
+                "line": line,
+            }));
+        }
+    });
+}
+
+/// 在应用启动时调用一次，开启后台轮询任务
+pub fn spawn_supervisor(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        // 探测超时、GC 停顿或服务冷启动都可能让单次 `/health` 探测落空，
+        // 要连续失败到这个次数才当成真的宕机去重启，而不是一次超时就杀活进程
+        const HEALTH_FAILURE_THRESHOLD: u32 = 3;
+
+        let mut backoff_secs = 1u64;
+        let mut consecutive_health_failures = 0u32;
+        loop {
+            tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+
+            let state = app_handle.state::<BridgeProcessState>();
+            if !state.running.load(Ordering::SeqCst) {
+                // 用户未请求运行，监督循环保持空闲
+                continue;
+            }
+
+            let exited_unexpectedly = {
+                let mut guard = match state.child.lock() {
+                    Ok(g) => g,
+                    Err(_) => continue,
+                };
+                match guard.as_mut().map(|c| c.try_wait()) {
+                    Some(Ok(Some(_))) => {
+                        *guard = None;
+                        true
+                    }
+                    Some(Ok(None)) => false,
+                    Some(Err(_)) | None => false,
+                }
+            };
+
+            let config: BridgeConfig = read_config(&get_config_path());
+            let health_ok = probe_health(&config).await;
+
+            if exited_unexpectedly {
+                consecutive_health_failures = 0;
+            } else if health_ok {
+                consecutive_health_failures = 0;
+                backoff_secs = 1;
+                continue;
+            } else {
+                consecutive_health_failures += 1;
+                if consecutive_health_failures < HEALTH_FAILURE_THRESHOLD {
+                    // 还没攒够连续失败次数，先不动现有进程，等下一轮探测
+                    continue;
+                }
+                consecutive_health_failures = 0;
+            }
+
+            // 子进程退出，或健康检查连续失败达到阈值 —— 按退避策略重启
+            let restart_count = state.restart_count.fetch_add(1, Ordering::SeqCst) + 1;
+            let max_restarts = 10u32; // 固定上限，不从 BridgeConfig 读取；超过后停止自动重启，避免无限重启刷屏
+            if restart_count > max_restarts {
+                mark_error(&state, "已达到最大重启次数，停止自动重启");
+                emit_event(&app_handle, &state, "restart-limit-exceeded");
+                state.running.store(false, Ordering::SeqCst);
+                continue;
+            }
+
+            mark_error(
+                &state,
+                if exited_unexpectedly {
+                    "桥接服务进程意外退出"
+                } else {
+                    "桥接服务健康检查失败"
+                },
+            );
+            emit_event(&app_handle, &state, "crashed");
+            tokio::time::sleep(std::time::Duration::from_secs(backoff_secs)).await;
+            backoff_secs = (backoff_secs * 2).min(60);
+
+            // 健康检查失败但旧进程仍存活时，先杀掉它再拉起新进程，
+            // 否则旧进程会残留并占住端口，导致新进程也起不来
+            if let Ok(mut guard) = state.child.lock() {
+                if let Some(mut old_child) = guard.take() {
+                    let _ = old_child.kill();
+                    let _ = old_child.wait();
+                }
+            }
+
+            match spawn_bridge_child(&app_handle) {
+                Ok(child) => {
+                    if let Ok(mut guard) = state.child.lock() {
+                        *guard = Some(child);
+                    }
+                    state.started_at.store(now_ts(), Ordering::SeqCst);
+                    if let Ok(mut guard) = state.last_error.lock() {
+                        *guard = None;
+                    }
+                    emit_event(&app_handle, &state, "restarted");
+                }
+                Err(e) => {
+                    mark_error(&state, format!("重启失败: {}", e));
+                    emit_event(&app_handle, &state, "restart-failed");
+                }
+            }
+        }
+    });
+}
+
+async fn probe_health(config: &BridgeConfig) -> bool {
+    let url = format!("http://{}:{}/health", config.host, config.port);
+    let client = build_client_with_timeout(config.proxy.as_deref(), std::time::Duration::from_secs(2));
+    client
+        .get(&url)
+        .send()
+        .await
+        .map(|r| r.status().is_success())
+        .unwrap_or(false)
+}
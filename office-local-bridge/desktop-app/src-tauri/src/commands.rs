@@ -2,10 +2,12 @@
 //!
 //! 定义前端可调用的 IPC 命令
 
+use crate::ai_client::{self, ChatMessage};
 use crate::config::*;
+use crate::provider_registry::ValidateProviderRequest;
+use futures::StreamExt;
 use serde::{Deserialize, Serialize};
-use std::sync::Mutex;
-use tauri::{Manager, State};
+use tauri::{Emitter, Manager, State};
 
 /// 桥接服务状态
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -15,6 +17,33 @@ pub struct BridgeStatus {
     pub port: u16,
     pub url: String,
     pub uptime: Option<i64>,
+    /// 监督循环自启动以来执行过的自动重启次数
+    pub restart_count: u32,
+    /// 最近一次崩溃或重启失败的原因，服务正常时为 `None`
+    pub last_error: Option<String>,
+}
+
+/// 可机器判断的错误类别，用于区分“未找到”“已存在”“连接失败”“超时”等
+/// 不同失败原因，而不是把它们都压进同一个 `error: String` 里
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum ErrorKind {
+    NotFound,
+    Conflict,
+    ConnectionFailed,
+    Timeout,
+    Upstream { status: u16 },
+    ParseError,
+    Cancelled,
+}
+
+/// 根据 reqwest 错误类型推断出结构化的 ErrorKind
+fn classify_reqwest_error(e: &reqwest::Error) -> ErrorKind {
+    if e.is_timeout() {
+        ErrorKind::Timeout
+    } else {
+        ErrorKind::ConnectionFailed
+    }
 }
 
 /// API 响应格式
@@ -23,6 +52,7 @@ pub struct ApiResponse<T> {
     pub success: bool,
     pub data: Option<T>,
     pub error: Option<String>,
+    pub code: Option<ErrorKind>,
 }
 
 impl<T> ApiResponse<T> {
@@ -31,6 +61,7 @@ impl<T> ApiResponse<T> {
             success: true,
             data: Some(data),
             error: None,
+            code: None,
         }
     }
 
@@ -39,12 +70,37 @@ impl<T> ApiResponse<T> {
             success: false,
             data: None,
             error: Some(message.to_string()),
+            code: None,
+        }
+    }
+
+    pub fn error_with_kind(message: &str, kind: ErrorKind) -> Self {
+        Self {
+            success: false,
+            data: None,
+            error: Some(message.to_string()),
+            code: Some(kind),
         }
     }
 }
 
 // ===== 配置管理命令 =====
 
+/// 对所有配置文件各跑一次迁移检查，返回每份配置升级了哪些步骤
+///
+/// `read_config` 在每次加载时已经透明地做过这件事，这个命令单纯是为了让
+/// 设置页能展示“刚刚升级了什么”，所以这里直接复用 `read_config_with_report`
+/// 丢弃具体的配置值，只要报告。
+#[tauri::command]
+pub fn migrate_config() -> Vec<crate::config_migration::MigrationReport> {
+    vec![
+        read_config_with_report::<BridgeConfig>(&get_config_path()).1,
+        read_config_with_report::<ProvidersConfig>(&get_providers_path()).1,
+        read_config_with_report::<ModelsConfig>(&get_models_path()).1,
+        read_config_with_report::<McpServersConfig>(&get_mcp_servers_path()).1,
+    ]
+}
+
 /// 获取主配置
 #[tauri::command]
 pub fn get_config() -> ApiResponse<BridgeConfig> {
@@ -103,23 +159,25 @@ pub fn update_config(config: serde_json::Value) -> ApiResponse<BridgeConfig> {
 /// 获取所有提供商
 #[tauri::command]
 pub fn get_providers() -> ApiResponse<Vec<AIProviderConfig>> {
-    let config: ProvidersConfig = read_config(&get_providers_path());
+    let config = read_providers_config();
     ApiResponse::success(config.providers)
 }
 
 /// 添加提供商
+///
+/// `api_key` 不落盘明文，由 `write_providers_config` 转存进系统密钥库。
 #[tauri::command]
 pub fn add_provider(provider: AIProviderConfig) -> ApiResponse<AIProviderConfig> {
-    let mut config: ProvidersConfig = read_config(&get_providers_path());
+    let mut config = read_providers_config();
 
     // 检查 ID 是否已存在
     if config.providers.iter().any(|p| p.id == provider.id) {
-        return ApiResponse::error("提供商 ID 已存在");
+        return ApiResponse::error_with_kind("提供商 ID 已存在", ErrorKind::Conflict);
     }
 
     config.providers.push(provider.clone());
 
-    match write_config(&get_providers_path(), &config) {
+    match write_providers_config(&config) {
         Ok(_) => ApiResponse::success(provider),
         Err(e) => ApiResponse::error(&e),
     }
@@ -128,40 +186,46 @@ pub fn add_provider(provider: AIProviderConfig) -> ApiResponse<AIProviderConfig>
 /// 更新提供商
 #[tauri::command]
 pub fn update_provider(provider: AIProviderConfig) -> ApiResponse<AIProviderConfig> {
-    let mut config: ProvidersConfig = read_config(&get_providers_path());
+    let mut config = read_providers_config();
 
     if let Some(index) = config.providers.iter().position(|p| p.id == provider.id) {
         config.providers[index] = provider.clone();
-        match write_config(&get_providers_path(), &config) {
+        match write_providers_config(&config) {
             Ok(_) => ApiResponse::success(provider),
             Err(e) => ApiResponse::error(&e),
         }
     } else {
-        ApiResponse::error("提供商不存在")
+        ApiResponse::error_with_kind("提供商不存在", ErrorKind::NotFound)
     }
 }
 
 /// 删除提供商
 #[tauri::command]
 pub fn delete_provider(id: String) -> ApiResponse<bool> {
-    let mut config: ProvidersConfig = read_config(&get_providers_path());
+    let mut config = read_providers_config();
     let original_len = config.providers.len();
     config.providers.retain(|p| p.id != id);
 
     if config.providers.len() == original_len {
-        return ApiResponse::error("提供商不存在");
+        return ApiResponse::error_with_kind("提供商不存在", ErrorKind::NotFound);
     }
 
-    match write_config(&get_providers_path(), &config) {
+    if let Err(e) = purge_provider_secret(&id) {
+        return ApiResponse::error(&e);
+    }
+
+    match write_providers_config(&config) {
         Ok(_) => ApiResponse::success(true),
         Err(e) => ApiResponse::error(&e),
     }
 }
 
-/// 测试提供商连接
-#[tauri::command]
-pub async fn test_provider_connection(provider: AIProviderConfig) -> ApiResponse<bool> {
-    // 根据提供商类型构建测试请求
+/// 按供应商类型构建“列出模型”请求：解析出 base_url、端点路径和鉴权头。
+/// `test_provider_connection` 和 `discover_models` 共用同一套拼接逻辑。
+fn build_models_list_request(
+    client: &reqwest::Client,
+    provider: &AIProviderConfig,
+) -> Result<reqwest::RequestBuilder, String> {
     let base_url = provider.base_url.clone().unwrap_or_else(|| {
         match provider.provider_type {
             AIProviderType::OpenAI => "https://api.openai.com/v1".to_string(),
@@ -173,10 +237,9 @@ pub async fn test_provider_connection(provider: AIProviderConfig) -> ApiResponse
     });
 
     if base_url.is_empty() {
-        return ApiResponse::error("未配置 API 端点");
+        return Err("未配置 API 端点".to_string());
     }
 
-    let client = reqwest::Client::new();
     let url = match provider.provider_type {
         AIProviderType::OpenAI => format!("{}/models", base_url),
         AIProviderType::Anthropic => format!("{}/v1/models", base_url),
@@ -184,14 +247,16 @@ pub async fn test_provider_connection(provider: AIProviderConfig) -> ApiResponse
         AIProviderType::Azure => format!(
             "{}/openai/models?api-version={}",
             base_url,
-            provider.azure_api_version.unwrap_or_else(|| "2024-02-15-preview".to_string())
+            provider
+                .azure_api_version
+                .clone()
+                .unwrap_or_else(|| "2024-02-15-preview".to_string())
         ),
         AIProviderType::Custom => format!("{}/models", base_url),
     };
 
     let mut request = client.get(&url);
 
-    // 设置认证头
     match provider.provider_type {
         AIProviderType::OpenAI | AIProviderType::Custom => {
             request = request.header("Authorization", format!("Bearer {}", provider.api_key));
@@ -208,18 +273,143 @@ pub async fn test_provider_connection(provider: AIProviderConfig) -> ApiResponse
         }
     }
 
+    Ok(request)
+}
+
+/// 测试提供商连接
+#[tauri::command]
+pub async fn test_provider_connection(provider: AIProviderConfig) -> ApiResponse<bool> {
+    let bridge_config: BridgeConfig = read_config(&get_config_path());
+    let proxy = resolve_proxy(provider.proxy.as_deref(), provider.proxy_inherit_env, &bridge_config);
+    let client = build_client(proxy.as_deref());
+
+    let request = match build_models_list_request(&client, &provider) {
+        Ok(request) => request,
+        Err(e) => return ApiResponse::error(&e),
+    };
+
     match request.send().await {
         Ok(response) => {
             if response.status().is_success() {
                 ApiResponse::success(true)
             } else {
-                ApiResponse::error(&format!("连接失败: HTTP {}", response.status()))
+                ApiResponse::error_with_kind(&format!("连接失败: HTTP {}", response.status()), ErrorKind::Upstream { status: response.status().as_u16() })
             }
         }
-        Err(e) => ApiResponse::error(&format!("连接失败: {}", e)),
+        Err(e) => ApiResponse::error_with_kind(&format!("连接失败: {}", e), classify_reqwest_error(&e)),
     }
 }
 
+/// 发现供应商可用模型并合并进本地模型列表
+///
+/// 复用 `test_provider_connection` 里同一套端点/鉴权拼接逻辑拉取模型列表，
+/// 再按各家返回格式（OpenAI/Anthropic 的 `data[].id`、Ollama 的
+/// `models[].name`、Azure 的 `value[].id`）归一化成 `ModelConfig`，跳过
+/// 已存在的 ID（与 `add_model` 的去重规则一致）后写回模型配置。
+#[tauri::command]
+pub async fn discover_models(provider_id: String) -> ApiResponse<Vec<ModelConfig>> {
+    let providers_config = read_providers_config();
+    let provider = match providers_config.providers.iter().find(|p| p.id == provider_id) {
+        Some(p) => p.clone(),
+        None => return ApiResponse::error_with_kind("提供商不存在", ErrorKind::NotFound),
+    };
+
+    let bridge_config: BridgeConfig = read_config(&get_config_path());
+    let proxy = resolve_proxy(provider.proxy.as_deref(), provider.proxy_inherit_env, &bridge_config);
+    let client = build_client(proxy.as_deref());
+
+    let request = match build_models_list_request(&client, &provider) {
+        Ok(request) => request,
+        Err(e) => return ApiResponse::error(&e),
+    };
+
+    let response = match request.send().await {
+        Ok(response) => response,
+        Err(e) => return ApiResponse::error_with_kind(&format!("请求失败: {}", e), classify_reqwest_error(&e)),
+    };
+
+    if !response.status().is_success() {
+        return ApiResponse::error_with_kind(
+            &format!("获取模型列表失败: HTTP {}", response.status()),
+            ErrorKind::Upstream { status: response.status().as_u16() },
+        );
+    }
+
+    let json: serde_json::Value = match response.json().await {
+        Ok(json) => json,
+        Err(e) => return ApiResponse::error_with_kind(&format!("解析响应失败: {}", e), ErrorKind::ParseError),
+    };
+
+    let discovered = normalize_discovered_models(&provider, &json);
+
+    let mut models_config: ModelsConfig = read_config(&get_models_path());
+    let mut added = vec![];
+    for model in discovered {
+        if !models_config.models.iter().any(|m| m.id == model.id) {
+            models_config.models.push(model.clone());
+            added.push(model);
+        }
+    }
+
+    match write_config(&get_models_path(), &models_config) {
+        Ok(_) => ApiResponse::success(added),
+        Err(e) => ApiResponse::error(&e),
+    }
+}
+
+/// 按供应商类型把模型列表响应归一化为 `ModelConfig`
+fn normalize_discovered_models(provider: &AIProviderConfig, json: &serde_json::Value) -> Vec<ModelConfig> {
+    let ids: Vec<String> = match provider.provider_type {
+        AIProviderType::OpenAI | AIProviderType::Anthropic | AIProviderType::Custom => json
+            .get("data")
+            .and_then(|d| d.as_array())
+            .map(|arr| {
+                arr.iter()
+                    .filter_map(|m| m.get("id").and_then(|id| id.as_str()).map(|s| s.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default(),
+        AIProviderType::Ollama => json
+            .get("models")
+            .and_then(|d| d.as_array())
+            .map(|arr| {
+                arr.iter()
+                    .filter_map(|m| m.get("name").and_then(|n| n.as_str()).map(|s| s.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default(),
+        AIProviderType::Azure => json
+            .get("value")
+            .and_then(|d| d.as_array())
+            .map(|arr| {
+                arr.iter()
+                    .filter_map(|m| m.get("id").and_then(|id| id.as_str()).map(|s| s.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default(),
+    };
+
+    ids.into_iter()
+        .map(|name| ModelConfig {
+            id: format!("{}:{}", provider.id, name),
+            provider_id: provider.id.clone(),
+            name: name.clone(),
+            display_name: name,
+            enabled: true,
+            is_default: false,
+            max_tokens: None,
+            temperature: None,
+            top_p: None,
+            frequency_penalty: None,
+            presence_penalty: None,
+            supports_vision: None,
+            supports_tools: None,
+            supports_streaming: None,
+            context_window: None,
+        })
+        .collect()
+}
+
 // ===== 模型管理命令 =====
 
 /// 获取所有模型
@@ -235,7 +425,7 @@ pub fn add_model(model: ModelConfig) -> ApiResponse<ModelConfig> {
     let mut config: ModelsConfig = read_config(&get_models_path());
 
     if config.models.iter().any(|m| m.id == model.id) {
-        return ApiResponse::error("模型 ID 已存在");
+        return ApiResponse::error_with_kind("模型 ID 已存在", ErrorKind::Conflict);
     }
 
     config.models.push(model.clone());
@@ -258,7 +448,7 @@ pub fn update_model(model: ModelConfig) -> ApiResponse<ModelConfig> {
             Err(e) => ApiResponse::error(&e),
         }
     } else {
-        ApiResponse::error("模型不存在")
+        ApiResponse::error_with_kind("模型不存在", ErrorKind::NotFound)
     }
 }
 
@@ -270,7 +460,7 @@ pub fn delete_model(id: String) -> ApiResponse<bool> {
     config.models.retain(|m| m.id != id);
 
     if config.models.len() == original_len {
-        return ApiResponse::error("模型不存在");
+        return ApiResponse::error_with_kind("模型不存在", ErrorKind::NotFound);
     }
 
     match write_config(&get_models_path(), &config) {
@@ -279,6 +469,54 @@ pub fn delete_model(id: String) -> ApiResponse<bool> {
     }
 }
 
+// ===== AI 客户端命令 =====
+
+/// 发起一次完整对话，返回整段回复
+#[tauri::command]
+pub async fn chat_completion(
+    provider: AIProviderConfig,
+    model_id: String,
+    messages: Vec<ChatMessage>,
+) -> ApiResponse<String> {
+    let client = ai_client::init(&provider);
+    match client.chat(&messages, &model_id).await {
+        Ok(text) => ApiResponse::success(text),
+        Err(e) => ApiResponse::error(&e),
+    }
+}
+
+/// 以流式方式发起对话，逐段通过 Tauri 事件推送给前端
+#[tauri::command]
+pub async fn chat_completion_stream(
+    window: tauri::Window,
+    provider: AIProviderConfig,
+    model_id: String,
+    messages: Vec<ChatMessage>,
+) -> ApiResponse<bool> {
+    let client = ai_client::init(&provider);
+    let mut stream = match client.chat_stream(&messages, &model_id).await {
+        Ok(stream) => stream,
+        Err(e) => return ApiResponse::error(&e),
+    };
+
+    while let Some(item) = stream.next().await {
+        match item {
+            Ok(chunk) => {
+                let _ = window.emit("office://chat-chunk", &chunk);
+                if chunk.done {
+                    break;
+                }
+            }
+            Err(e) => {
+                let _ = window.emit("office://chat-error", &e);
+                return ApiResponse::error(&e);
+            }
+        }
+    }
+
+    ApiResponse::success(true)
+}
+
 // ===== MCP 服务器命令 =====
 
 /// 获取所有 MCP 服务器配置
@@ -295,12 +533,12 @@ pub fn add_mcp_server(server: McpServerConfig) -> ApiResponse<McpServerConfig> {
 
     // 检查 ID 是否已存在
     if config.servers.iter().any(|s| s.id == server.id) {
-        return ApiResponse::error("MCP 服务器 ID 已存在");
+        return ApiResponse::error_with_kind("MCP 服务器 ID 已存在", ErrorKind::Conflict);
     }
 
     // 检查名称是否已存在
     if config.servers.iter().any(|s| s.name == server.name) {
-        return ApiResponse::error("MCP 服务器名称已存在");
+        return ApiResponse::error_with_kind("MCP 服务器名称已存在", ErrorKind::Conflict);
     }
 
     config.servers.push(server.clone());
@@ -319,7 +557,7 @@ pub fn update_mcp_server(server: McpServerConfig) -> ApiResponse<McpServerConfig
     if let Some(index) = config.servers.iter().position(|s| s.id == server.id) {
         // 检查名称是否与其他服务器重复
         if config.servers.iter().any(|s| s.id != server.id && s.name == server.name) {
-            return ApiResponse::error("MCP 服务器名称已存在");
+            return ApiResponse::error_with_kind("MCP 服务器名称已存在", ErrorKind::Conflict);
         }
         
         config.servers[index] = server.clone();
@@ -328,7 +566,7 @@ pub fn update_mcp_server(server: McpServerConfig) -> ApiResponse<McpServerConfig
             Err(e) => ApiResponse::error(&e),
         }
     } else {
-        ApiResponse::error("MCP 服务器不存在")
+        ApiResponse::error_with_kind("MCP 服务器不存在", ErrorKind::NotFound)
     }
 }
 
@@ -342,10 +580,7 @@ pub async fn delete_mcp_server(id: String) -> ApiResponse<bool> {
         config_main.host, config_main.port, id
     );
 
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(5))
-        .build()
-        .unwrap_or_default();
+    let client = build_client_with_timeout(config_main.proxy.as_deref(), std::time::Duration::from_secs(5));
 
     // 尝试停止服务器，忽略错误（服务器可能未运行）
     let _ = client.post(&stop_url).send().await;
@@ -356,7 +591,7 @@ pub async fn delete_mcp_server(id: String) -> ApiResponse<bool> {
     config.servers.retain(|s| s.id != id);
 
     if config.servers.len() == original_len {
-        return ApiResponse::error("MCP 服务器不存在");
+        return ApiResponse::error_with_kind("MCP 服务器不存在", ErrorKind::NotFound);
     }
 
     match write_config(&get_mcp_servers_path(), &config) {
@@ -372,10 +607,7 @@ pub async fn get_mcp_server_status() -> ApiResponse<Vec<McpServerStatus>> {
     // 直接使用 /api/mcp/servers 端点获取服务器状态
     let url = format!("http://{}:{}/api/mcp/servers", config.host, config.port);
 
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(5))
-        .build()
-        .unwrap_or_default();
+    let client = build_client_with_timeout(config.proxy.as_deref(), std::time::Duration::from_secs(5));
 
     match client.get(&url).send().await {
         Ok(response) => {
@@ -399,79 +631,82 @@ pub async fn get_mcp_server_status() -> ApiResponse<Vec<McpServerStatus>> {
                             }
                         }
                     }
-                    Err(e) => ApiResponse::error(&format!("解析响应失败: {}", e)),
+                    Err(e) => ApiResponse::error_with_kind(&format!("解析响应失败: {}", e), ErrorKind::ParseError),
                 }
             } else {
-                ApiResponse::error(&format!("服务请求失败: HTTP {}", response.status()))
+                ApiResponse::error_with_kind(&format!("服务请求失败: HTTP {}", response.status()), ErrorKind::Upstream { status: response.status().as_u16() })
             }
         }
-        Err(e) => ApiResponse::error(&format!("无法连接到服务: {}", e)),
+        Err(e) => ApiResponse::error_with_kind(&format!("无法连接到服务: {}", e), classify_reqwest_error(&e)),
     }
 }
 
-/// 启动 MCP 服务器
+/// 启动 MCP 服务器，成功后顺带推一条 `office://log`，不用等轮询任务下一轮才有反馈
 #[tauri::command]
-pub async fn start_mcp_server(id: String) -> ApiResponse<bool> {
+pub async fn start_mcp_server(app: tauri::AppHandle, id: String) -> ApiResponse<bool> {
     let config: BridgeConfig = read_config(&get_config_path());
     let url = format!(
         "http://{}:{}/api/mcp/servers/{}/start",
         config.host, config.port, id
     );
 
-    let client = reqwest::Client::new();
+    let client = build_client(config.proxy.as_deref());
     match client.post(&url).send().await {
         Ok(response) => {
             if response.status().is_success() {
+                crate::mcp_status_stream::emit_mcp_log(&app, &id, "服务器已启动");
                 ApiResponse::success(true)
             } else {
-                ApiResponse::error(&format!("启动失败: HTTP {}", response.status()))
+                ApiResponse::error_with_kind(&format!("启动失败: HTTP {}", response.status()), ErrorKind::Upstream { status: response.status().as_u16() })
             }
         }
-        Err(e) => ApiResponse::error(&format!("请求失败: {}", e)),
+        Err(e) => ApiResponse::error_with_kind(&format!("请求失败: {}", e), classify_reqwest_error(&e)),
     }
 }
 
-/// 停止 MCP 服务器
+/// 停止 MCP 服务器，成功后顺带推一条 `office://log`
 #[tauri::command]
-pub async fn stop_mcp_server(id: String) -> ApiResponse<bool> {
+pub async fn stop_mcp_server(app: tauri::AppHandle, id: String) -> ApiResponse<bool> {
     let config: BridgeConfig = read_config(&get_config_path());
     let url = format!(
         "http://{}:{}/api/mcp/servers/{}/stop",
         config.host, config.port, id
     );
 
-    let client = reqwest::Client::new();
+    let client = build_client(config.proxy.as_deref());
     match client.post(&url).send().await {
         Ok(response) => {
             if response.status().is_success() {
+                crate::mcp_status_stream::emit_mcp_log(&app, &id, "服务器已停止");
                 ApiResponse::success(true)
             } else {
-                ApiResponse::error(&format!("停止失败: HTTP {}", response.status()))
+                ApiResponse::error_with_kind(&format!("停止失败: HTTP {}", response.status()), ErrorKind::Upstream { status: response.status().as_u16() })
             }
         }
-        Err(e) => ApiResponse::error(&format!("请求失败: {}", e)),
+        Err(e) => ApiResponse::error_with_kind(&format!("请求失败: {}", e), classify_reqwest_error(&e)),
     }
 }
 
-/// 重启 MCP 服务器
+/// 重启 MCP 服务器，成功后顺带推一条 `office://log`
 #[tauri::command]
-pub async fn restart_mcp_server(id: String) -> ApiResponse<bool> {
+pub async fn restart_mcp_server(app: tauri::AppHandle, id: String) -> ApiResponse<bool> {
     let config: BridgeConfig = read_config(&get_config_path());
     let url = format!(
         "http://{}:{}/api/mcp/servers/{}/restart",
         config.host, config.port, id
     );
 
-    let client = reqwest::Client::new();
+    let client = build_client(config.proxy.as_deref());
     match client.post(&url).send().await {
         Ok(response) => {
             if response.status().is_success() {
+                crate::mcp_status_stream::emit_mcp_log(&app, &id, "服务器已重启");
                 ApiResponse::success(true)
             } else {
-                ApiResponse::error(&format!("重启失败: HTTP {}", response.status()))
+                ApiResponse::error_with_kind(&format!("重启失败: HTTP {}", response.status()), ErrorKind::Upstream { status: response.status().as_u16() })
             }
         }
-        Err(e) => ApiResponse::error(&format!("请求失败: {}", e)),
+        Err(e) => ApiResponse::error_with_kind(&format!("请求失败: {}", e), classify_reqwest_error(&e)),
     }
 }
 
@@ -495,7 +730,7 @@ pub async fn get_mcp_server_tools(id: String) -> ApiResponse<Vec<McpTool>> {
         config.host, config.port, id
     );
 
-    let client = reqwest::Client::new();
+    let client = build_client(config.proxy.as_deref());
     match client.get(&url).send().await {
         Ok(response) => {
             if response.status().is_success() {
@@ -510,13 +745,13 @@ pub async fn get_mcp_server_tools(id: String) -> ApiResponse<Vec<McpTool>> {
                             ApiResponse::success(vec![])
                         }
                     }
-                    Err(e) => ApiResponse::error(&format!("解析响应失败: {}", e)),
+                    Err(e) => ApiResponse::error_with_kind(&format!("解析响应失败: {}", e), ErrorKind::ParseError),
                 }
             } else {
-                ApiResponse::error(&format!("获取工具失败: HTTP {}", response.status()))
+                ApiResponse::error_with_kind(&format!("获取工具失败: HTTP {}", response.status()), ErrorKind::Upstream { status: response.status().as_u16() })
             }
         }
-        Err(e) => ApiResponse::error(&format!("请求失败: {}", e)),
+        Err(e) => ApiResponse::error_with_kind(&format!("请求失败: {}", e), classify_reqwest_error(&e)),
     }
 }
 
@@ -549,7 +784,7 @@ pub async fn get_logs(limit: Option<u32>, level: Option<String>) -> ApiResponse<
         url = format!("{}?{}", url, params.join("&"));
     }
 
-    let client = reqwest::Client::new();
+    let client = build_client(config.proxy.as_deref());
     match client.get(&url).send().await {
         Ok(response) => {
             if response.status().is_success() {
@@ -569,31 +804,31 @@ pub async fn get_logs(limit: Option<u32>, level: Option<String>) -> ApiResponse<
                             ApiResponse::success(vec![])
                         }
                     }
-                    Err(e) => ApiResponse::error(&format!("解析响应失败: {}", e)),
+                    Err(e) => ApiResponse::error_with_kind(&format!("解析响应失败: {}", e), ErrorKind::ParseError),
                 }
             } else {
-                ApiResponse::error(&format!("获取日志失败: HTTP {}", response.status()))
+                ApiResponse::error_with_kind(&format!("获取日志失败: HTTP {}", response.status()), ErrorKind::Upstream { status: response.status().as_u16() })
             }
         }
-        Err(e) => ApiResponse::error(&format!("请求失败: {}", e)),
+        Err(e) => ApiResponse::error_with_kind(&format!("请求失败: {}", e), classify_reqwest_error(&e)),
     }
 }
 
 // ===== 桥接服务命令 =====
 
-/// 桥接服务进程状态
-pub struct BridgeProcessState(pub Mutex<Option<std::process::Child>>);
-
-/// 获取桥接服务状态
+/// 获取桥接服务状态：既实时探测 `/health`，也带上监督循环记录的重启次数
+/// 和最近一次失败原因，让界面能在用户点击“验证供应商”之前就提示后端不可达
 #[tauri::command]
-pub async fn get_bridge_status() -> ApiResponse<BridgeStatus> {
+pub async fn get_bridge_status(
+    state: State<'_, crate::bridge_supervisor::BridgeProcessState>,
+) -> ApiResponse<BridgeStatus> {
     let config: BridgeConfig = read_config(&get_config_path());
     let url = format!("http://{}:{}/health", config.host, config.port);
 
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(2))
-        .build()
-        .unwrap_or_default();
+    let restart_count = state.restart_count.load(std::sync::atomic::Ordering::SeqCst);
+    let last_error = state.last_error.lock().ok().and_then(|g| g.clone());
+
+    let client = build_client_with_timeout(config.proxy.as_deref(), std::time::Duration::from_secs(2));
 
     match client.get(&url).send().await {
         Ok(response) => {
@@ -608,6 +843,8 @@ pub async fn get_bridge_status() -> ApiResponse<BridgeStatus> {
                     port: config.port,
                     url: format!("http://{}:{}", config.host, config.port),
                     uptime,
+                    restart_count,
+                    last_error,
                 })
             } else {
                 ApiResponse::success(BridgeStatus {
@@ -615,6 +852,8 @@ pub async fn get_bridge_status() -> ApiResponse<BridgeStatus> {
                     port: config.port,
                     url: format!("http://{}:{}", config.host, config.port),
                     uptime: None,
+                    restart_count,
+                    last_error,
                 })
             }
         }
@@ -623,6 +862,8 @@ pub async fn get_bridge_status() -> ApiResponse<BridgeStatus> {
             port: config.port,
             url: format!("http://{}:{}", config.host, config.port),
             uptime: None,
+            restart_count,
+            last_error,
         }),
     }
 }
@@ -630,12 +871,10 @@ pub async fn get_bridge_status() -> ApiResponse<BridgeStatus> {
 /// 启动桥接服务
 #[tauri::command]
 pub async fn start_bridge_service(
-    state: State<'_, BridgeProcessState>,
+    state: State<'_, crate::bridge_supervisor::BridgeProcessState>,
     app_handle: tauri::AppHandle,
 ) -> Result<ApiResponse<bool>, String> {
-    use std::process::{Command, Stdio};
-
-    let mut process_guard = state.0.lock().map_err(|e| e.to_string())?;
+    let mut process_guard = state.child.lock().map_err(|e| e.to_string())?;
 
     // 检查是否已运行
     if let Some(ref mut child) = *process_guard {
@@ -645,7 +884,7 @@ pub async fn start_bridge_service(
                 *process_guard = None;
             }
             Ok(None) => {
-                return Ok(ApiResponse::error("桥接服务已在运行中"));
+                return Ok(ApiResponse::error_with_kind("桥接服务已在运行中", ErrorKind::Conflict));
             }
             Err(e) => {
                 return Ok(ApiResponse::error(&format!("检查进程状态失败: {}", e)));
@@ -653,23 +892,10 @@ pub async fn start_bridge_service(
         }
     }
 
-    // 获取服务路径
-    let service_path = get_bridge_service_path(&app_handle);
-
-    // 确定启动命令
-    let (cmd, args, cwd) = get_bridge_start_command(&service_path);
-
-    // 启动进程
-    match Command::new(&cmd)
-        .args(&args)
-        .current_dir(&cwd)
-        .stdin(Stdio::null())
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .spawn()
-    {
+    match crate::bridge_supervisor::spawn_bridge_child(&app_handle) {
         Ok(child) => {
             *process_guard = Some(child);
+            crate::bridge_supervisor::mark_started(&state, &app_handle);
             Ok(ApiResponse::success(true))
         }
         Err(e) => Ok(ApiResponse::error(&format!("启动服务失败: {}", e))),
@@ -679,9 +905,13 @@ pub async fn start_bridge_service(
 /// 停止桥接服务
 #[tauri::command]
 pub async fn stop_bridge_service(
-    state: State<'_, BridgeProcessState>,
+    state: State<'_, crate::bridge_supervisor::BridgeProcessState>,
+    app_handle: tauri::AppHandle,
 ) -> Result<ApiResponse<bool>, String> {
-    let mut process_guard = state.0.lock().map_err(|e| e.to_string())?;
+    // 主动停止，避免被监督循环当成崩溃而自动重启
+    crate::bridge_supervisor::mark_stopping(&state);
+
+    let mut process_guard = state.child.lock().map_err(|e| e.to_string())?;
 
     if let Some(ref mut child) = *process_guard {
         // Windows 上使用 taskkill
@@ -702,15 +932,53 @@ pub async fn stop_bridge_service(
         // 等待进程退出
         let _ = child.wait();
         *process_guard = None;
+        crate::bridge_supervisor::mark_stopped(&state, &app_handle);
 
         Ok(ApiResponse::success(true))
     } else {
-        Ok(ApiResponse::error("桥接服务未运行"))
+        Ok(ApiResponse::error_with_kind("桥接服务未运行", ErrorKind::NotFound))
+    }
+}
+
+/// 重启桥接服务：若已在运行先停掉旧进程，再拉起新的
+#[tauri::command]
+pub async fn restart_bridge_service(
+    state: State<'_, crate::bridge_supervisor::BridgeProcessState>,
+    app_handle: tauri::AppHandle,
+) -> Result<ApiResponse<bool>, String> {
+    {
+        crate::bridge_supervisor::mark_stopping(&state);
+        let mut process_guard = state.child.lock().map_err(|e| e.to_string())?;
+        if let Some(ref mut child) = *process_guard {
+            #[cfg(windows)]
+            {
+                let pid = child.id();
+                let _ = std::process::Command::new("taskkill")
+                    .args(["/PID", &pid.to_string(), "/F"])
+                    .output();
+            }
+            #[cfg(not(windows))]
+            {
+                let _ = child.kill();
+            }
+            let _ = child.wait();
+        }
+        *process_guard = None;
+    }
+
+    let mut process_guard = state.child.lock().map_err(|e| e.to_string())?;
+    match crate::bridge_supervisor::spawn_bridge_child(&app_handle) {
+        Ok(child) => {
+            *process_guard = Some(child);
+            crate::bridge_supervisor::mark_started(&state, &app_handle);
+            Ok(ApiResponse::success(true))
+        }
+        Err(e) => Ok(ApiResponse::error(&format!("重启服务失败: {}", e))),
     }
 }
 
 /// 获取桥接服务路径
-fn get_bridge_service_path(app_handle: &tauri::AppHandle) -> std::path::PathBuf {
+pub(crate) fn get_bridge_service_path(app_handle: &tauri::AppHandle) -> std::path::PathBuf {
     // 优先使用环境变量
     if let Ok(path) = std::env::var("BRIDGE_SERVICE_PATH") {
         return std::path::PathBuf::from(path);
@@ -735,7 +1003,7 @@ fn get_bridge_service_path(app_handle: &tauri::AppHandle) -> std::path::PathBuf
 }
 
 /// 获取启动命令
-fn get_bridge_start_command(service_path: &std::path::Path) -> (String, Vec<String>, std::path::PathBuf) {
+pub(crate) fn get_bridge_start_command(service_path: &std::path::Path) -> (String, Vec<String>, std::path::PathBuf) {
     // 优先使用打包后的可执行文件
     #[cfg(windows)]
     let exe_name = "office-local-bridge-win.exe";
@@ -784,19 +1052,6 @@ pub struct ModelInfo {
     pub supports_streaming: Option<bool>,
 }
 
-/// 验证供应商请求
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct ValidateProviderRequest {
-    #[serde(rename = "type")]
-    pub provider_type: AIProviderType,
-    pub api_key: String,
-    pub base_url: Option<String>,
-    pub azure_endpoint: Option<String>,
-    pub azure_deployment: Option<String>,
-    pub azure_api_version: Option<String>,
-}
-
 /// 验证供应商响应
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -825,137 +1080,268 @@ pub struct TestModelResponse {
 }
 
 /// 验证供应商配置（通过 Bridge 服务 API）
+///
+/// 信封解包、重试退避都已经收敛进 `bridge_request`，这里只负责组装
+/// 该供应商 variant 自己的请求体。`provider_id` 仅在校验一个已保存的供应商
+/// （编辑流程）时传入，用来把顺带拿到的模型列表预热进本地缓存；新建供应商
+/// 尚无 id 时传 `None` 即可，只是少了这次预热，不影响校验本身。
 #[tauri::command]
-pub async fn validate_provider(config: ValidateProviderRequest) -> ApiResponse<ValidateProviderResponse> {
-    let bridge_config: BridgeConfig = read_config(&get_config_path());
-    let url = format!(
-        "http://{}:{}/api/config/providers/validate",
-        bridge_config.host, bridge_config.port
-    );
-
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(30))
-        .build()
-        .unwrap_or_default();
-
-    // 构建请求体
-    let body = serde_json::json!({
-        "type": config.provider_type,
-        "apiKey": config.api_key,
-        "baseUrl": config.base_url,
-        "azureEndpoint": config.azure_endpoint,
-        "azureDeployment": config.azure_deployment,
-        "azureApiVersion": config.azure_api_version,
-    });
+pub async fn validate_provider(
+    provider_id: Option<String>,
+    config: ValidateProviderRequest,
+) -> ApiResponse<ValidateProviderResponse> {
+    let body = config.build_validate_body();
+    let response: ApiResponse<ValidateProviderResponse> = crate::bridge_client::bridge_request(
+        reqwest::Method::POST,
+        "/api/config/providers/validate",
+        Some(body),
+    )
+    .await;
 
-    match client.post(&url).json(&body).send().await {
-        Ok(response) => {
-            if response.status().is_success() {
-                match response.json::<serde_json::Value>().await {
-                    Ok(json) => {
-                        // 后端返回格式: { success: true, data: { valid: true, models: [...] } }
-                        if let Some(data) = json.get("data") {
-                            match serde_json::from_value::<ValidateProviderResponse>(data.clone()) {
-                                Ok(result) => ApiResponse::success(result),
-                                Err(e) => ApiResponse::error(&format!("解析响应失败: {}", e)),
-                            }
-                        } else {
-                            ApiResponse::error("响应格式错误: 缺少 data 字段")
-                        }
-                    }
-                    Err(e) => ApiResponse::error(&format!("解析响应失败: {}", e)),
-                }
-            } else {
-                let status = response.status();
-                let error_text = response.text().await.unwrap_or_default();
-                ApiResponse::error(&format!("验证失败: HTTP {} - {}", status, error_text))
+    // 验证顺带拿到的模型列表直接预热缓存，省得设置面板打开时还要再请求一次
+    if let Some(id) = &provider_id {
+        if let Some(result) = &response.data {
+            if let Some(models) = &result.models {
+                crate::model_cache::warm_cache(id, models.clone());
             }
         }
-        Err(e) => ApiResponse::error(&format!("请求失败: {}", e)),
     }
+
+    response
 }
 
-/// 获取供应商可用模型列表（通过 Bridge 服务 API）
+/// `GET .../models` 信封里 `data` 字段的实际形状
+#[derive(Debug, Deserialize)]
+struct ModelsEnvelope {
+    models: Vec<ModelInfo>,
+}
+
+/// 获取供应商可用模型列表（通过 Bridge 服务 API），结果会预热本地缓存
 #[tauri::command]
-pub async fn get_provider_models(provider_id: String) -> ApiResponse<serde_json::Value> {
-    let bridge_config: BridgeConfig = read_config(&get_config_path());
-    let url = format!(
-        "http://{}:{}/api/config/providers/{}/models",
-        bridge_config.host, bridge_config.port, provider_id
-    );
+pub async fn get_provider_models(provider_id: String) -> ApiResponse<Vec<ModelInfo>> {
+    let response: ApiResponse<ModelsEnvelope> = crate::bridge_client::bridge_request(
+        reqwest::Method::GET,
+        &format!("/api/config/providers/{}/models", provider_id),
+        None,
+    )
+    .await;
 
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(30))
-        .build()
-        .unwrap_or_default();
+    let response = ApiResponse {
+        success: response.success,
+        data: response.data.map(|envelope| envelope.models),
+        error: response.error,
+        code: response.code,
+    };
 
-    match client.get(&url).send().await {
-        Ok(response) => {
-            if response.status().is_success() {
-                match response.json::<serde_json::Value>().await {
-                    Ok(json) => {
-                        // 后端返回格式: { success: true, data: { models: [...] } }
-                        if let Some(data) = json.get("data") {
-                            ApiResponse::success(data.clone())
-                        } else {
-                            // 兼容直接返回 { models: [...] } 的格式
-                            ApiResponse::success(json)
-                        }
-                    }
-                    Err(e) => ApiResponse::error(&format!("解析响应失败: {}", e)),
-                }
-            } else {
-                let status = response.status();
-                let error_text = response.text().await.unwrap_or_default();
-                ApiResponse::error(&format!("获取模型列表失败: HTTP {} - {}", status, error_text))
+    if let Some(models) = &response.data {
+        crate::model_cache::warm_cache(&provider_id, models.clone());
+    }
+
+    response
+}
+
+/// 查询某个供应商的模型列表，支持按能力（视觉/工具调用/流式/上下文窗口）筛选
+///
+/// 默认优先读取未过期的本地缓存；传 `filter.forceRefresh = true` 或缓存
+/// 不存在/已过期时才会向 Bridge 重新请求并刷新缓存。
+#[tauri::command]
+pub async fn query_models(
+    provider_id: String,
+    filter: Option<crate::model_cache::ModelFilter>,
+) -> ApiResponse<Vec<ModelInfo>> {
+    let filter = filter.unwrap_or_default();
+
+    let models = if filter.force_refresh.unwrap_or(false) {
+        None
+    } else {
+        crate::model_cache::load_cached_models(&provider_id)
+    };
+
+    let models = match models {
+        Some(models) => models,
+        None => {
+            let response = get_provider_models(provider_id).await;
+            match response.data {
+                Some(models) => models,
+                None => return ApiResponse { success: false, data: None, error: response.error, code: response.code },
             }
         }
-        Err(e) => ApiResponse::error(&format!("请求失败: {}", e)),
-    }
+    };
+
+    let filtered = models
+        .into_iter()
+        .filter(|m| crate::model_cache::matches_filter(m, &filter))
+        .collect();
+
+    ApiResponse::success(filtered)
 }
 
 /// 测试特定模型（通过 Bridge 服务 API）
 #[tauri::command]
 pub async fn test_model(provider_id: String, request: TestModelRequest) -> ApiResponse<TestModelResponse> {
+    let body = serde_json::json!({
+        "modelId": request.model_id,
+        "testMessage": request.test_message,
+    });
+
+    crate::bridge_client::bridge_request(
+        reqwest::Method::POST,
+        &format!("/api/config/providers/{}/test-model", provider_id),
+        Some(body),
+    )
+    .await
+}
+
+/// 流式测试事件：逐段转发给前端的 token 增量，以及结束时携带总延迟的终止事件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StreamModelChunk {
+    pub delta: String,
+    pub done: bool,
+    pub latency: Option<i64>,
+}
+
+/// `stream_model` 的取消标记：前端在用户主动打断时调用 `cancel_stream_model`
+/// 置位，`stream_model` 每收到一个网络分片就检查一次，发现置位就提前退出
+/// 并返回 `ErrorKind::Cancelled`，和请求失败/超时区分开
+#[derive(Default)]
+pub struct StreamModelState {
+    cancelled: std::sync::atomic::AtomicBool,
+}
+
+/// 取消正在进行的 `stream_model` 流式测试
+#[tauri::command]
+pub fn cancel_stream_model(state: tauri::State<'_, StreamModelState>) -> ApiResponse<bool> {
+    state.cancelled.store(true, std::sync::atomic::Ordering::SeqCst);
+    ApiResponse::success(true)
+}
+
+/// 以流式方式测试模型：逐 token 转发增量内容，而不是等整段回复生成完才返回。
+///
+/// 如果 `ModelInfo.supports_streaming` 为 `false`，调用方应当退回到缓冲式的
+/// `test_model`；这里只负责流式路径本身。
+#[tauri::command]
+pub async fn stream_model(
+    window: tauri::Window,
+    state: tauri::State<'_, StreamModelState>,
+    provider_id: String,
+    request: TestModelRequest,
+) -> ApiResponse<bool> {
+    state.cancelled.store(false, std::sync::atomic::Ordering::SeqCst);
     let bridge_config: BridgeConfig = read_config(&get_config_path());
     let url = format!(
         "http://{}:{}/api/config/providers/{}/test-model",
         bridge_config.host, bridge_config.port, provider_id
     );
 
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(60))
-        .build()
-        .unwrap_or_default();
+    let client = build_client_with_timeout(bridge_config.proxy.as_deref(), std::time::Duration::from_secs(120));
 
     let body = serde_json::json!({
         "modelId": request.model_id,
         "testMessage": request.test_message,
+        "stream": true,
     });
 
-    match client.post(&url).json(&body).send().await {
-        Ok(response) => {
-            if response.status().is_success() {
-                match response.json::<serde_json::Value>().await {
+    let start = std::time::Instant::now();
+
+    let response = match client
+        .post(&url)
+        .header("Accept", "text/event-stream")
+        .json(&body)
+        .send()
+        .await
+    {
+        Ok(response) => response,
+        Err(e) => return ApiResponse::error_with_kind(&format!("请求失败: {}", e), classify_reqwest_error(&e)),
+    };
+
+    if !response.status().is_success() {
+        let status = response.status();
+        return ApiResponse::error_with_kind(
+            &format!("流式测试模型失败: HTTP {}", status),
+            ErrorKind::Upstream { status: status.as_u16() },
+        );
+    }
+
+    let mut byte_stream = response.bytes_stream();
+    let mut utf8_buf = crate::utf8_buffer::Utf8ChunkBuffer::default();
+    let mut leftover = String::new();
+
+    while let Some(chunk) = byte_stream.next().await {
+        if state.cancelled.load(std::sync::atomic::Ordering::SeqCst) {
+            let _ = window.emit(
+                "office://stream-model-chunk",
+                StreamModelChunk {
+                    delta: String::new(),
+                    done: true,
+                    latency: Some(start.elapsed().as_millis() as i64),
+                },
+            );
+            return ApiResponse::error_with_kind("流式测试已取消", ErrorKind::Cancelled);
+        }
+
+        let chunk = match chunk {
+            Ok(chunk) => chunk,
+            Err(e) => {
+                let _ = window.emit("office://stream-model-error", &format!("读取流失败: {}", e));
+                return ApiResponse::error_with_kind(&format!("读取流失败: {}", e), classify_reqwest_error(&e));
+            }
+        };
+        leftover.push_str(&utf8_buf.push(&chunk));
+
+        // SSE 以空行分隔事件，逐条取出完整的 "data: ..." 帧处理，
+        // 不完整的尾部留到下次读取时再拼接
+        while let Some(pos) = leftover.find("\n\n") {
+            let event = leftover[..pos].to_string();
+            leftover.drain(..pos + 2);
+
+            for line in event.lines() {
+                let Some(data) = line.strip_prefix("data:") else { continue };
+                let data = data.trim();
+
+                if data == "[DONE]" {
+                    let _ = window.emit(
+                        "office://stream-model-chunk",
+                        StreamModelChunk {
+                            delta: String::new(),
+                            done: true,
+                            latency: Some(start.elapsed().as_millis() as i64),
+                        },
+                    );
+                    return ApiResponse::success(true);
+                }
+
+                match serde_json::from_str::<serde_json::Value>(data) {
                     Ok(json) => {
-                        // 后端返回格式: { success: true, data: { success: true, response: "...", latency: 123 } }
-                        if let Some(data) = json.get("data") {
-                            match serde_json::from_value::<TestModelResponse>(data.clone()) {
-                                Ok(result) => ApiResponse::success(result),
-                                Err(e) => ApiResponse::error(&format!("解析响应失败: {}", e)),
-                            }
-                        } else {
-                            ApiResponse::error("响应格式错误: 缺少 data 字段")
-                        }
+                        let delta = json
+                            .get("delta")
+                            .and_then(|d| d.as_str())
+                            .unwrap_or_default()
+                            .to_string();
+                        let _ = window.emit(
+                            "office://stream-model-chunk",
+                            StreamModelChunk {
+                                delta,
+                                done: false,
+                                latency: None,
+                            },
+                        );
+                    }
+                    Err(e) => {
+                        let _ = window.emit("office://stream-model-error", &format!("解析流事件失败: {}", e));
                     }
-                    Err(e) => ApiResponse::error(&format!("解析响应失败: {}", e)),
                 }
-            } else {
-                let status = response.status();
-                let error_text = response.text().await.unwrap_or_default();
-                ApiResponse::error(&format!("测试模型失败: HTTP {} - {}", status, error_text))
             }
         }
-        Err(e) => ApiResponse::error(&format!("请求失败: {}", e)),
     }
+
+    let _ = window.emit(
+        "office://stream-model-chunk",
+        StreamModelChunk {
+            delta: String::new(),
+            done: true,
+            latency: Some(start.elapsed().as_millis() as i64),
+        },
+    );
+    ApiResponse::success(true)
 }
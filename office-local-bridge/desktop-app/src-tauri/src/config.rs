@@ -88,6 +88,10 @@ pub struct AIProviderConfig {
     pub is_default: bool,
     pub api_key: String,
     pub base_url: Option<String>,
+    // 该供应商专属的代理地址（http://、https:// 或 socks5://），不设置则使用全局代理
+    pub proxy: Option<String>,
+    // 为 true 且未设置 `proxy` 时，从 `ALL_PROXY`/`HTTPS_PROXY` 环境变量读取代理地址
+    pub proxy_inherit_env: Option<bool>,
     // Azure 特有
     pub azure_endpoint: Option<String>,
     pub azure_deployment: Option<String>,
@@ -137,6 +141,13 @@ pub struct McpServerConfig {
     pub env: Option<std::collections::HashMap<String, String>>,
     pub enabled: bool,
     pub auto_start: bool,
+    /// 自动重启的最大尝试次数，超过后监督循环放弃并推一条 `office://log`；
+    /// 状态里的 `last_error` 不由桌面端写入
+    pub max_restarts: Option<u32>,
+    /// 重启退避的上限（秒），指数退避到这个值后不再增长
+    pub backoff_ceiling_secs: Option<u64>,
+    /// 健康探测的间隔（秒），探测内容是状态轮询加一次 `tools/list` 往返
+    pub health_check_interval_secs: Option<u32>,
 }
 
 /// MCP 服务器状态
@@ -165,12 +176,31 @@ pub struct BridgeConfig {
     pub default_embedding_model_id: Option<String>, // 默认嵌入模型 (格式: providerId:modelId)
     pub auto_start: bool,
     pub minimize_to_tray: bool,
+    // 全局代理地址，供未单独设置 proxy 的供应商和 Bridge 出站请求回退使用
+    pub proxy: Option<String>,
+    // 为 true 时，`resolve_proxy` 对未单独设置 proxy 的供应商先从 `ALL_PROXY`/`HTTPS_PROXY`
+    // 环境变量读取代理地址，环境变量为空才回退到上面的 `proxy`
+    pub proxy_inherit_env: bool,
+    // 对 Bridge 的请求在连接错误/5xx 上的最大重试次数
+    pub retry_count: u32,
+    // 重试退避的基础延迟（毫秒），按 2^attempt 指数增长
+    pub retry_base_delay_ms: u64,
+    // 对 Bridge 请求的默认超时（秒）
+    pub request_timeout_secs: u64,
+    // 自更新订阅的发布渠道（如 "stable"/"beta"）
+    pub update_channel: String,
+    // 自动检查更新的间隔（小时），0 表示关闭自动检查
+    pub update_auto_check_interval_hours: u32,
+    // 发现新版本后是否在后台静默下载，等待用户手动触发安装
+    pub update_download_in_background: bool,
 }
 
 impl Default for BridgeConfig {
     fn default() -> Self {
         Self {
-            version: 1,
+            // v2：引入代理继承、Bridge 请求重试/超时、自更新这几批字段后的当前
+            // schema 版本，老版本文件由 config_migration::migrate 升级上来
+            version: 2,
             port: 3001,
             host: "localhost".to_string(),
             log_level: "info".to_string(),
@@ -179,6 +209,14 @@ impl Default for BridgeConfig {
             default_embedding_model_id: None,
             auto_start: true,
             minimize_to_tray: true,
+            proxy: None,
+            proxy_inherit_env: false,
+            retry_count: 2,
+            retry_base_delay_ms: 300,
+            request_timeout_secs: 30,
+            update_channel: "stable".to_string(),
+            update_auto_check_interval_hours: 24,
+            update_download_in_background: true,
         }
     }
 }
@@ -193,7 +231,9 @@ pub struct ProvidersConfig {
 impl Default for ProvidersConfig {
     fn default() -> Self {
         Self {
-            version: 1,
+            // v2：引入代理支持和密钥库迁移后的当前 schema 版本，
+            // 老版本文件由 config_migration::migrate 升级上来
+            version: 2,
             providers: vec![],
         }
     }
@@ -225,18 +265,177 @@ pub struct McpServersConfig {
 impl Default for McpServersConfig {
     fn default() -> Self {
         Self {
-            version: 1,
+            version: 2,
             servers: vec![],
         }
     }
 }
 
+/// 读取配置文件，返回解析结果和这次加载期间执行了哪些迁移步骤的报告
+///
+/// 流程：宽松解析成 `serde_json::Value` -> 按文件名 + `version` 字段跑
+/// 注册的升级步骤 -> 反序列化成目标类型。文件不存在时直接返回默认值；
+/// 文件存在但解析不出合法 JSON、或升级后仍无法反序列化时，原样备份成
+/// `<name>.corrupt.<timestamp>.json` 再回退默认值，而不是静默丢弃。
+pub fn read_config_with_report<T: for<'de> Deserialize<'de> + Default>(
+    path: &PathBuf,
+) -> (T, crate::config_migration::MigrationReport) {
+    let config_name = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("config")
+        .to_string();
+
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(_) => return (T::default(), crate::config_migration::MigrationReport::empty(&config_name)),
+    };
+
+    let value: serde_json::Value = match serde_json::from_str(&content) {
+        Ok(value) => value,
+        Err(e) => {
+            eprintln!("解析配置文件 {} 失败: {}", path.display(), e);
+            crate::config_migration::backup_corrupt_file(path, &content);
+            return (T::default(), crate::config_migration::MigrationReport::empty(&config_name));
+        }
+    };
+
+    let (migrated, report) = crate::config_migration::migrate(&config_name, value);
+    if !report.applied.is_empty() {
+        eprintln!(
+            "配置 {} 已从 v{:?} 升级到 v{:?}: {}",
+            config_name, report.from_version, report.to_version, report.applied.join("; ")
+        );
+        let _ = write_config(path, &migrated);
+    }
+
+    match serde_json::from_value(migrated) {
+        Ok(parsed) => (parsed, report),
+        Err(e) => {
+            eprintln!("配置 {} 升级后仍无法解析: {}", config_name, e);
+            crate::config_migration::backup_corrupt_file(path, &content);
+            (T::default(), report)
+        }
+    }
+}
+
 /// 读取配置文件
 pub fn read_config<T: for<'de> Deserialize<'de> + Default>(path: &PathBuf) -> T {
-    match fs::read_to_string(path) {
-        Ok(content) => serde_json::from_str(&content).unwrap_or_default(),
-        Err(_) => T::default(),
+    read_config_with_report(path).0
+}
+
+/// 读取提供商配置，并把各条目的 API Key 从系统密钥库里补回内存
+///
+/// 兼容旧版明文 `providers.json`：发现某条目 `api_key` 非空就说明还没
+/// 迁移过，顺手存进密钥库、清空文件里的明文并重写一次，之后才统一从
+/// 密钥库读回实际值，调用方拿到的 `AIProviderConfig` 和迁移前一样可用。
+/// 存密钥库失败（密钥库不可用/被锁）时保留明文不清空，避免在没有地方
+/// 存着这份密钥的情况下把它从磁盘上抹掉。
+pub fn read_providers_config() -> ProvidersConfig {
+    let mut config: ProvidersConfig = read_config(&get_providers_path());
+    let mut migrated = false;
+
+    for provider in &mut config.providers {
+        if !provider.api_key.is_empty() {
+            match crate::secrets::store_api_key(&provider.id, &provider.api_key) {
+                Ok(()) => {
+                    provider.api_key = String::new();
+                    migrated = true;
+                }
+                Err(e) => {
+                    eprintln!("供应商 {} 的 API Key 迁移到密钥库失败，暂时保留明文: {}", provider.id, e);
+                }
+            }
+        }
+    }
+
+    if migrated {
+        let _ = write_config(&get_providers_path(), &config);
+    }
+
+    for provider in &mut config.providers {
+        // 明文仍非空说明上面迁移失败、保留了原值，这里不应该拿密钥库里的
+        // 空结果把它覆盖掉
+        if provider.api_key.is_empty() {
+            provider.api_key = crate::secrets::load_api_key(&provider.id);
+        }
+    }
+
+    config
+}
+
+/// 写入提供商配置：API Key 存进系统密钥库，`providers.json` 里只留空字符串占位
+pub fn write_providers_config(config: &ProvidersConfig) -> Result<(), String> {
+    let mut sanitized = config.clone();
+    for provider in &mut sanitized.providers {
+        crate::secrets::store_api_key(&provider.id, &provider.api_key)?;
+        provider.api_key = String::new();
+    }
+    write_config(&get_providers_path(), &sanitized)
+}
+
+/// 供应商被删除时，顺带清掉密钥库里的对应条目
+pub fn purge_provider_secret(provider_id: &str) -> Result<(), String> {
+    crate::secrets::delete_api_key(provider_id)
+}
+
+/// 从 `ALL_PROXY`/`HTTPS_PROXY` 环境变量读取代理地址（大小写均识别），
+/// 对齐 curl/大多数网络工具的约定，也是 Tauri bundler 下载时识别 SOCKS
+/// 代理所用的同一套变量
+fn env_proxy() -> Option<String> {
+    ["ALL_PROXY", "all_proxy", "HTTPS_PROXY", "https_proxy"]
+        .iter()
+        .find_map(|name| std::env::var(name).ok().filter(|v| !v.is_empty()))
+}
+
+/// 按优先级解析某个供应商实际应该使用的代理地址：
+/// 1. 供应商自己设置的 `proxy`
+/// 2. 供应商或全局开启了 `proxy_inherit_env` 时，从环境变量读取
+/// 3. 全局 `BridgeConfig.proxy` 回退
+pub fn resolve_proxy(provider_proxy: Option<&str>, provider_inherit_env: Option<bool>, bridge_config: &BridgeConfig) -> Option<String> {
+    if let Some(proxy) = provider_proxy {
+        if !proxy.is_empty() {
+            return Some(proxy.to_string());
+        }
+    }
+
+    if provider_inherit_env.unwrap_or(false) || bridge_config.proxy_inherit_env {
+        if let Some(proxy) = env_proxy() {
+            return Some(proxy);
+        }
+    }
+
+    bridge_config.proxy.clone()
+}
+
+/// 构建一个（可选地）带代理的 reqwest 客户端
+///
+/// `proxy` 支持 `http://`、`https://`、`socks5://` 形式的地址；传 `None`
+/// 或地址解析失败时回退为不带代理的默认客户端，不让代理配置错误直接
+/// 阻断请求发送。
+pub fn build_client(proxy: Option<&str>) -> reqwest::Client {
+    let mut builder = reqwest::Client::builder();
+    if let Some(proxy_url) = proxy {
+        if !proxy_url.is_empty() {
+            if let Ok(proxy) = reqwest::Proxy::all(proxy_url) {
+                builder = builder.proxy(proxy);
+            }
+        }
+    }
+    builder.build().unwrap_or_default()
+}
+
+/// 构建一个带超时和（可选）代理的 reqwest 客户端
+pub fn build_client_with_timeout(proxy: Option<&str>, timeout: std::time::Duration) -> reqwest::Client {
+    let mut builder = reqwest::Client::builder().timeout(timeout);
+    if let Some(proxy_url) = proxy {
+        if !proxy_url.is_empty() {
+            if let Ok(proxy) = reqwest::Proxy::all(proxy_url) {
+                builder = builder.proxy(proxy);
+            }
+        }
     }
+    builder.build().unwrap_or_default()
 }
 
 /// 写入配置文件
@@ -0,0 +1,305 @@
+//! 配置迁移框架
+//!
+//! `read_config` 以前是 `serde_json::from_str(..).unwrap_or_default()`，
+//! 字段一改、文件一损坏就直接静默丢弃用户的全部配置——而每份配置文件
+//! 早就带着 `version` 字段，只是从来没被用来做升级。这里把加载流程拆成
+//! 三步：先宽松解析成 `serde_json::Value`，按 `version` 字段依次跑注册
+//! 的升级步骤把 JSON 转换到当前版本，最后才反序列化成目标类型；文件本身
+//! 解析不出合法 JSON 时，原样备份到 `<name>.corrupt.<timestamp>.json`
+//! 并记录警告，而不是让用户的数据凭空消失。
+
+use serde_json::Value;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 一次版本升级：把 `from_version` 的 JSON 转换成 `from_version + 1`
+pub struct MigrationStep {
+    pub from_version: i32,
+    pub description: &'static str,
+    pub apply: fn(Value) -> Value,
+}
+
+/// config.json v1 -> v2：补上代理继承、Bridge 请求重试/超时、自更新这几批
+/// 改动新增的字段，老文件没有这些 key 同样会在反序列化阶段出错，被
+/// `unwrap_or_default()` 静默清空（连带丢失 port/host/默认供应商等原有配置）
+fn config_v1_to_v2(mut value: Value) -> Value {
+    if let Some(obj) = value.as_object_mut() {
+        obj.entry("proxyInheritEnv").or_insert(Value::from(false));
+        obj.entry("retryCount").or_insert(Value::from(2));
+        obj.entry("retryBaseDelayMs").or_insert(Value::from(300));
+        obj.entry("requestTimeoutSecs").or_insert(Value::from(30));
+        obj.entry("updateChannel").or_insert(Value::from("stable"));
+        obj.entry("updateAutoCheckIntervalHours").or_insert(Value::from(24));
+        obj.entry("updateDownloadInBackground").or_insert(Value::from(true));
+    }
+    bump_version(value, 2)
+}
+
+static CONFIG_MIGRATIONS: [MigrationStep; 1] = [MigrationStep {
+    from_version: 1,
+    description: "补上 proxyInheritEnv/retryCount/retryBaseDelayMs/requestTimeoutSecs/updateChannel/updateAutoCheckIntervalHours/updateDownloadInBackground 字段",
+    apply: config_v1_to_v2,
+}];
+
+/// providers.json v1 -> v2：给每个条目显式补上 proxy/proxyInheritEnv/
+/// connectionStatus/lastTestedAt（值为 null）并把版本号升到 2。这几个字段
+/// 都是 `Option`，缺 key 时反序列化本来就得到 `None`，这一步不改变解析结果，
+/// 只让文件内容与当前 schema 对齐
+fn providers_v1_to_v2(mut value: Value) -> Value {
+    if let Some(providers) = value.get_mut("providers").and_then(|p| p.as_array_mut()) {
+        for provider in providers {
+            if let Some(obj) = provider.as_object_mut() {
+                obj.entry("proxy").or_insert(Value::Null);
+                obj.entry("proxyInheritEnv").or_insert(Value::Null);
+                obj.entry("connectionStatus").or_insert(Value::Null);
+                obj.entry("lastTestedAt").or_insert(Value::Null);
+            }
+        }
+    }
+    bump_version(value, 2)
+}
+
+static PROVIDERS_MIGRATIONS: [MigrationStep; 1] = [MigrationStep {
+    from_version: 1,
+    description: "补上 proxy/proxyInheritEnv/connectionStatus/lastTestedAt 字段",
+    apply: providers_v1_to_v2,
+}];
+
+/// mcp-servers.json v1 -> v2：给每个条目显式补上监督子系统引入的 maxRestarts/
+/// backoffCeilingSecs/healthCheckIntervalSecs 旋钮（值为 null）并把版本号升到 2。
+/// 这几个字段都是 `Option`，缺 key 时反序列化本来就得到 `None`（即使用默认值）
+fn mcp_servers_v1_to_v2(mut value: Value) -> Value {
+    if let Some(servers) = value.get_mut("servers").and_then(|s| s.as_array_mut()) {
+        for server in servers {
+            if let Some(obj) = server.as_object_mut() {
+                obj.entry("maxRestarts").or_insert(Value::Null);
+                obj.entry("backoffCeilingSecs").or_insert(Value::Null);
+                obj.entry("healthCheckIntervalSecs").or_insert(Value::Null);
+            }
+        }
+    }
+    bump_version(value, 2)
+}
+
+static MCP_SERVERS_MIGRATIONS: [MigrationStep; 1] = [MigrationStep {
+    from_version: 1,
+    description: "补上 maxRestarts/backoffCeilingSecs/healthCheckIntervalSecs 字段",
+    apply: mcp_servers_v1_to_v2,
+}];
+
+/// 按配置文件名返回其注册的升级链，数组按 `from_version` 升序排列
+fn migrations_for(config_name: &str) -> &'static [MigrationStep] {
+    match config_name {
+        "config" => &CONFIG_MIGRATIONS,
+        "providers" => &PROVIDERS_MIGRATIONS,
+        "mcp-servers" => &MCP_SERVERS_MIGRATIONS,
+        _ => &[],
+    }
+}
+
+fn bump_version(mut value: Value, to_version: i32) -> Value {
+    if let Some(obj) = value.as_object_mut() {
+        obj.insert("version".to_string(), Value::from(to_version));
+    }
+    value
+}
+
+/// 读出 JSON 里的 `version` 字段，缺失或非整数时按 1（最早的无版本 schema）处理
+fn read_version(value: &Value) -> i32 {
+    value.get("version").and_then(|v| v.as_i64()).unwrap_or(1) as i32
+}
+
+/// 一次迁移运行的报告，供 `migrate_config` 命令展示给用户哪些配置被升级过
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MigrationReport {
+    pub config_name: String,
+    pub from_version: Option<i32>,
+    pub to_version: Option<i32>,
+    pub applied: Vec<String>,
+}
+
+impl MigrationReport {
+    /// 文件不存在或解析失败时用的空报告
+    pub fn empty(config_name: &str) -> Self {
+        Self {
+            config_name: config_name.to_string(),
+            from_version: None,
+            to_version: None,
+            applied: vec![],
+        }
+    }
+}
+
+/// 依次跑完所有适用的升级步骤，返回升级后的 `Value` 和执行报告
+pub fn migrate(config_name: &str, mut value: Value) -> (Value, MigrationReport) {
+    let from_version = read_version(&value);
+    let mut current_version = from_version;
+    let mut applied = Vec::new();
+
+    while let Some(step) = migrations_for(config_name)
+        .iter()
+        .find(|step| step.from_version == current_version)
+    {
+        value = (step.apply)(value);
+        applied.push(step.description.to_string());
+        current_version = read_version(&value);
+    }
+
+    (
+        value,
+        MigrationReport {
+            config_name: config_name.to_string(),
+            from_version: Some(from_version),
+            to_version: Some(current_version),
+            applied,
+        },
+    )
+}
+
+/// 把解析失败的配置文件原样备份到同目录下的 `<name>.corrupt.<timestamp>.json`
+pub fn backup_corrupt_file(path: &Path, content: &str) {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("config");
+    let backup_path = path.with_file_name(format!("{}.corrupt.{}.json", stem, timestamp));
+
+    match std::fs::write(&backup_path, content) {
+        Ok(_) => eprintln!("配置文件 {} 解析失败，已备份到 {}", path.display(), backup_path.display()),
+        Err(e) => eprintln!("配置文件 {} 解析失败，备份也失败了: {}", path.display(), e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn migrates_providers_v1_to_v2_and_backfills_missing_fields() {
+        // v1 schema：引入代理/密钥库支持之前的 providers.json 快照
+        let v1 = json!({
+            "version": 1,
+            "providers": [
+                {
+                    "id": "openai-1",
+                    "type": "openai",
+                    "name": "OpenAI",
+                    "enabled": true,
+                    "isDefault": true,
+                    "apiKey": "sk-test",
+                    "baseUrl": null,
+                    "azureEndpoint": null,
+                    "azureDeployment": null,
+                    "azureApiVersion": null,
+                    "customHeaders": null,
+                    "selectedModels": null
+                }
+            ]
+        });
+
+        let (migrated, report) = migrate("providers", v1);
+
+        assert_eq!(report.from_version, Some(1));
+        assert_eq!(report.to_version, Some(2));
+        assert_eq!(report.applied.len(), 1);
+        assert_eq!(migrated["version"], json!(2));
+
+        let provider = &migrated["providers"][0];
+        assert_eq!(provider["id"], json!("openai-1"));
+        assert_eq!(provider["proxy"], Value::Null);
+        assert_eq!(provider["proxyInheritEnv"], Value::Null);
+        assert_eq!(provider["connectionStatus"], Value::Null);
+        assert_eq!(provider["lastTestedAt"], Value::Null);
+    }
+
+    #[test]
+    fn already_current_version_runs_no_migrations() {
+        let v2 = json!({ "version": 2, "providers": [] });
+        let (migrated, report) = migrate("providers", v2.clone());
+
+        assert_eq!(report.from_version, Some(2));
+        assert_eq!(report.to_version, Some(2));
+        assert!(report.applied.is_empty());
+        assert_eq!(migrated, v2);
+    }
+
+    #[test]
+    fn unversioned_file_is_treated_as_v1() {
+        let unversioned = json!({ "providers": [] });
+        let (_, report) = migrate("providers", unversioned);
+        assert_eq!(report.from_version, Some(1));
+    }
+
+    #[test]
+    fn unknown_config_name_has_no_registered_migrations() {
+        let value = json!({ "version": 1 });
+        let (migrated, report) = migrate("models", value.clone());
+        assert!(report.applied.is_empty());
+        assert_eq!(migrated, value);
+    }
+
+    #[test]
+    fn migrates_config_v1_to_v2_and_backfills_retry_and_update_fields() {
+        // v1 schema：代理继承/重试退避/自更新这几批改动之前的 config.json 快照
+        let v1 = json!({
+            "version": 1,
+            "port": 3001,
+            "host": "localhost",
+            "logLevel": "info",
+            "defaultProviderId": "openai-1",
+            "autoStart": true,
+            "minimizeToTray": true,
+            "proxy": null
+        });
+
+        let (migrated, report) = migrate("config", v1);
+
+        assert_eq!(report.from_version, Some(1));
+        assert_eq!(report.to_version, Some(2));
+        assert_eq!(report.applied.len(), 1);
+        assert_eq!(migrated["version"], json!(2));
+        assert_eq!(migrated["port"], json!(3001));
+        assert_eq!(migrated["defaultProviderId"], json!("openai-1"));
+        assert_eq!(migrated["proxyInheritEnv"], json!(false));
+        assert_eq!(migrated["retryCount"], json!(2));
+        assert_eq!(migrated["retryBaseDelayMs"], json!(300));
+        assert_eq!(migrated["requestTimeoutSecs"], json!(30));
+        assert_eq!(migrated["updateChannel"], json!("stable"));
+        assert_eq!(migrated["updateAutoCheckIntervalHours"], json!(24));
+        assert_eq!(migrated["updateDownloadInBackground"], json!(true));
+    }
+
+    #[test]
+    fn migrates_mcp_servers_v1_to_v2_and_backfills_supervisor_knobs() {
+        let v1 = json!({
+            "version": 1,
+            "servers": [
+                {
+                    "id": "fs-server",
+                    "name": "文件系统",
+                    "command": "npx",
+                    "args": ["-y", "@modelcontextprotocol/server-filesystem"],
+                    "cwd": null,
+                    "env": null,
+                    "enabled": true,
+                    "autoStart": true
+                }
+            ]
+        });
+
+        let (migrated, report) = migrate("mcp-servers", v1);
+
+        assert_eq!(report.from_version, Some(1));
+        assert_eq!(report.to_version, Some(2));
+        assert_eq!(migrated["version"], json!(2));
+
+        let server = &migrated["servers"][0];
+        assert_eq!(server["id"], json!("fs-server"));
+        assert_eq!(server["maxRestarts"], Value::Null);
+        assert_eq!(server["backoffCeilingSecs"], Value::Null);
+        assert_eq!(server["healthCheckIntervalSecs"], Value::Null);
+    }
+}
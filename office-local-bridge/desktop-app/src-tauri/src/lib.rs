@@ -2,16 +2,37 @@
 //!
 //! 提供配置管理、进程管理和系统集成功能
 
-use std::sync::Mutex;
 use tauri::Manager;
 
+mod ai_client;
+mod bridge_client;
+mod bridge_supervisor;
 mod commands;
 mod config;
+mod config_migration;
 mod autostart;
+mod log_stream;
+mod mcp_status_stream;
+mod mcp_supervisor;
+mod model_cache;
+mod provider_registry;
+mod secrets;
+mod updater;
+mod utf8_buffer;
 
+pub use ai_client::*;
+pub use bridge_client::*;
+pub use bridge_supervisor::*;
 pub use commands::*;
 pub use config::*;
+pub use config_migration::*;
 pub use autostart::*;
+pub use log_stream::*;
+pub use mcp_status_stream::*;
+pub use mcp_supervisor::*;
+pub use model_cache::*;
+pub use provider_registry::*;
+pub use updater::*;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -21,24 +42,40 @@ pub fn run() {
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_process::init())
         .plugin(tauri_plugin_autostart::init(tauri_plugin_autostart::MacosLauncher::LaunchAgent, Some(vec!["--minimized"])))
-        .manage(commands::BridgeProcessState(Mutex::new(None)))
+        .plugin(tauri_plugin_updater::Builder::new().build())
+        .manage(bridge_supervisor::BridgeProcessState::default())
+        .manage(log_stream::LogStreamState::default())
+        .manage(mcp_status_stream::McpStatusStreamState::default())
+        .manage(mcp_supervisor::McpSupervisorState::default())
+        .manage(commands::StreamModelState::default())
         .setup(|app| {
             // 设置系统托盘
             setup_tray(app)?;
+            // 启动桥接服务监督循环，自动检测崩溃并重启
+            bridge_supervisor::spawn_supervisor(app.handle().clone());
+            // 启动 MCP 服务器监督循环，自动检测崩溃并按退避策略重启
+            mcp_supervisor::spawn_mcp_supervisor(app.handle().clone());
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             commands::get_config,
             commands::save_config,
             commands::update_config,
+            commands::migrate_config,
+            commands::chat_completion,
+            commands::chat_completion_stream,
             commands::get_providers,
             commands::add_provider,
             commands::update_provider,
             commands::delete_provider,
             commands::test_provider_connection,
+            commands::discover_models,
             commands::validate_provider,
             commands::get_provider_models,
+            commands::query_models,
             commands::test_model,
+            commands::stream_model,
+            commands::cancel_stream_model,
             commands::get_models,
             commands::add_model,
             commands::update_model,
@@ -53,15 +90,46 @@ pub fn run() {
             commands::restart_mcp_server,
             commands::get_mcp_server_tools,
             commands::get_logs,
+            log_stream::subscribe_logs,
+            log_stream::unsubscribe_logs,
+            mcp_status_stream::subscribe_mcp_status,
+            mcp_status_stream::unsubscribe_mcp_status,
             commands::get_bridge_status,
             commands::start_bridge_service,
             commands::stop_bridge_service,
+            commands::restart_bridge_service,
             autostart::enable_autostart,
             autostart::disable_autostart,
             autostart::is_autostart_enabled,
+            updater::check_for_update,
+            updater::install_update,
         ])
-        .run(tauri::generate_context!())
-        .expect("启动应用失败");
+        .build(tauri::generate_context!())
+        .expect("启动应用失败")
+        .run(|app_handle, event| {
+            // 应用退出时把桥接服务子进程清理掉，不依赖 OS 收尾；MCP 服务器本身
+            // 由 Bridge 进程管理，Bridge 退出时它们会跟着一起清理
+            if let tauri::RunEvent::Exit = event {
+                let state = app_handle.state::<bridge_supervisor::BridgeProcessState>();
+                bridge_supervisor::mark_stopping(&state);
+                if let Ok(mut guard) = state.child.lock() {
+                    if let Some(mut child) = guard.take() {
+                        #[cfg(windows)]
+                        {
+                            let pid = child.id();
+                            let _ = std::process::Command::new("taskkill")
+                                .args(["/PID", &pid.to_string(), "/F"])
+                                .output();
+                        }
+                        #[cfg(not(windows))]
+                        {
+                            let _ = child.kill();
+                        }
+                        let _ = child.wait();
+                    }
+                }
+            }
+        });
 }
 
 /// 设置系统托盘
@@ -70,8 +138,9 @@ fn setup_tray(app: &tauri::App) -> Result<(), Box<dyn std::error::Error>> {
     use tauri::menu::{Menu, MenuItem};
 
     let show_item = MenuItem::with_id(app, "show", "显示窗口", true, None::<&str>)?;
+    let check_update_item = MenuItem::with_id(app, "check-update", "检查更新", true, None::<&str>)?;
     let quit_item = MenuItem::with_id(app, "quit", "退出", true, None::<&str>)?;
-    let menu = Menu::with_items(app, &[&show_item, &quit_item])?;
+    let menu = Menu::with_items(app, &[&show_item, &check_update_item, &quit_item])?;
 
     let _tray = TrayIconBuilder::new()
         .menu(&menu)
@@ -83,6 +152,14 @@ fn setup_tray(app: &tauri::App) -> Result<(), Box<dyn std::error::Error>> {
                     let _ = window.set_focus();
                 }
             }
+            "check-update" => {
+                // 菜单事件没有返回值通道，检查结果由 check_for_update 内部
+                // 通过 office://update-available 事件推给前端
+                let app_handle = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    let _ = crate::updater::check_for_update(app_handle).await;
+                });
+            }
             "quit" => {
                 app.exit(0);
             }
@@ -0,0 +1,129 @@
+//! 日志推送子系统
+//!
+//! `get_logs` 只能一次性拉取当前日志，前端想保持最新就得不断轮询。这里改为
+//! `subscribe_logs` 打开一条到 Bridge 流式日志端点的长连接，把收到的每条
+//! `LogEntry` 通过 Tauri 事件转发给前端，并维护一个有界环形缓冲区，
+//! 让刚订阅的窗口能立刻收到最近的历史日志，而不必等下一条新日志到达。
+
+use crate::commands::{ApiResponse, LogEntry};
+use crate::config::{build_client, get_config_path, read_config, BridgeConfig};
+use futures::StreamExt;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::task::JoinHandle;
+
+/// 环形缓冲区能保留的最近日志条数
+const RING_BUFFER_CAPACITY: usize = 500;
+
+/// 日志订阅状态：历史缓冲区 + 当前订阅任务句柄
+#[derive(Default)]
+pub struct LogStreamState {
+    buffer: Mutex<VecDeque<LogEntry>>,
+    task: Mutex<Option<JoinHandle<()>>>,
+}
+
+fn push_to_buffer(buffer: &Mutex<VecDeque<LogEntry>>, entry: LogEntry) {
+    if let Ok(mut guard) = buffer.lock() {
+        if guard.len() >= RING_BUFFER_CAPACITY {
+            guard.pop_front();
+        }
+        guard.push_back(entry);
+    }
+}
+
+/// 订阅日志流：先回放环形缓冲区里的历史记录，再开始转发实时日志
+#[tauri::command]
+pub async fn subscribe_logs(
+    window: tauri::Window,
+    state: tauri::State<'_, LogStreamState>,
+    level: Option<String>,
+) -> Result<ApiResponse<bool>, String> {
+    // 回放最近历史，避免刚订阅的窗口要等到下一条日志才有内容
+    if let Ok(buffer) = state.buffer.lock() {
+        for entry in buffer.iter() {
+            if level.as_deref().map_or(true, |lv| entry.level == lv) {
+                let _ = window.emit("office://log", entry);
+            }
+        }
+    }
+
+    // 同一窗口重复订阅时，先停掉旧的转发任务
+    if let Ok(mut task_guard) = state.task.lock() {
+        if let Some(handle) = task_guard.take() {
+            handle.abort();
+        }
+    }
+
+    let app_handle = window.app_handle().clone();
+    let bridge_config: BridgeConfig = read_config(&get_config_path());
+
+    let handle = tauri::async_runtime::spawn(async move {
+        let _ = stream_logs(app_handle, bridge_config, level).await;
+    });
+
+    if let Ok(mut task_guard) = state.task.lock() {
+        *task_guard = Some(handle);
+    }
+
+    Ok(ApiResponse::success(true))
+}
+
+/// 取消日志订阅
+#[tauri::command]
+pub fn unsubscribe_logs(state: tauri::State<'_, LogStreamState>) -> ApiResponse<bool> {
+    if let Ok(mut task_guard) = state.task.lock() {
+        if let Some(handle) = task_guard.take() {
+            handle.abort();
+        }
+    }
+    ApiResponse::success(true)
+}
+
+/// 打开 Bridge 的流式日志端点（chunked NDJSON），每收到一行就解析转发
+async fn stream_logs(app_handle: AppHandle, config: BridgeConfig, level: Option<String>) -> Result<(), String> {
+    let mut url = format!("http://{}:{}/api/logs/stream", config.host, config.port);
+    if let Some(lv) = &level {
+        url = format!("{}?level={}", url, lv);
+    }
+
+    let client = build_client(config.proxy.as_deref());
+    let response = client
+        .get(&url)
+        .header("Accept", "application/x-ndjson")
+        .send()
+        .await
+        .map_err(|e| format!("连接日志流失败: {}", e))?;
+
+    if !response.status().is_success() {
+        return Err(format!("日志流请求失败: HTTP {}", response.status()));
+    }
+
+    let mut byte_stream = response.bytes_stream();
+    let mut utf8_buf = crate::utf8_buffer::Utf8ChunkBuffer::default();
+    let mut leftover = String::new();
+
+    let state = app_handle.state::<LogStreamState>();
+
+    while let Some(chunk) = byte_stream.next().await {
+        let chunk = chunk.map_err(|e| format!("读取日志流失败: {}", e))?;
+        leftover.push_str(&utf8_buf.push(&chunk));
+
+        while let Some(pos) = leftover.find('\n') {
+            let line = leftover[..pos].trim().to_string();
+            leftover.drain(..=pos);
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Ok(entry) = serde_json::from_str::<LogEntry>(&line) {
+                push_to_buffer(&state.buffer, entry.clone());
+                if level.as_deref().map_or(true, |lv| entry.level == lv) {
+                    let _ = app_handle.emit("office://log", &entry);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,120 @@
+//! MCP 服务器状态推送子系统
+//!
+//! 前端以前只能反复调 `get_mcp_server_status`/`get_logs` 才能发现某个 MCP
+//! 服务器的状态变化。MCP 服务器本身是由 Bridge 服务管理、经 HTTP 暴露的，
+//! 并不是桌面端直接 spawn 的子进程，所以这里没有 stdout/stderr 管道可读，
+//! 而是起一个后台轮询任务，定期拉取 `/api/mcp/servers`，和上一次的快照逐个
+//! 比对 `status`/`toolCount`，只把真正变化的条目通过 `office://mcp-status`
+//! 推给前端；`start_mcp_server`/`stop_mcp_server`/`restart_mcp_server` 在拿到
+//! Bridge 响应后也会顺带推一条 `office://log`，不用等下一轮轮询才看到反馈。
+//! 多窗口场景下用 `emit_filter` 只序列化一次 payload 再分发给每个窗口，而不是
+//! 对每个窗口各序列化一次。
+
+use crate::commands::{ApiResponse, LogEntry, McpServerStatus};
+use crate::config::{build_client_with_timeout, get_config_path, read_config, BridgeConfig};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::task::JoinHandle;
+
+/// 两次轮询之间的间隔
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// MCP 状态订阅状态：上一次看到的快照 + 当前轮询任务句柄
+#[derive(Default)]
+pub struct McpStatusStreamState {
+    last_known: Mutex<HashMap<String, McpServerStatus>>,
+    task: Mutex<Option<JoinHandle<()>>>,
+}
+
+fn now_ts() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// 把一条状态变化推给所有窗口，payload 只序列化一次
+fn emit_status(app_handle: &AppHandle, status: &McpServerStatus) {
+    let _ = app_handle.emit_filter("office://mcp-status", status, |_| true);
+}
+
+/// 推一条 MCP 相关日志，复用既有的 `office://log` 通道
+pub fn emit_mcp_log(app_handle: &AppHandle, server_id: &str, message: &str) {
+    let entry = LogEntry {
+        timestamp: now_ts(),
+        level: "info".to_string(),
+        module: format!("mcp:{}", server_id),
+        message: message.to_string(),
+        data: None,
+    };
+    let _ = app_handle.emit_filter("office://log", &entry, |_| true);
+}
+
+/// 订阅 MCP 服务器状态：启动后台轮询任务，发现变化就推送事件
+#[tauri::command]
+pub async fn subscribe_mcp_status(
+    window: tauri::Window,
+    state: tauri::State<'_, McpStatusStreamState>,
+) -> Result<ApiResponse<bool>, String> {
+    // 同一窗口重复订阅时，先停掉旧的轮询任务
+    if let Ok(mut task_guard) = state.task.lock() {
+        if let Some(handle) = task_guard.take() {
+            handle.abort();
+        }
+    }
+
+    let app_handle = window.app_handle().clone();
+    let handle = tauri::async_runtime::spawn(async move {
+        poll_mcp_status(app_handle).await;
+    });
+
+    if let Ok(mut task_guard) = state.task.lock() {
+        *task_guard = Some(handle);
+    }
+
+    Ok(ApiResponse::success(true))
+}
+
+/// 取消 MCP 状态订阅
+#[tauri::command]
+pub fn unsubscribe_mcp_status(state: tauri::State<'_, McpStatusStreamState>) -> ApiResponse<bool> {
+    if let Ok(mut task_guard) = state.task.lock() {
+        if let Some(handle) = task_guard.take() {
+            handle.abort();
+        }
+    }
+    ApiResponse::success(true)
+}
+
+/// 定期向 Bridge 拉取 MCP 服务器状态，和上一次快照逐个比对，只推送变化的条目
+async fn poll_mcp_status(app_handle: AppHandle) {
+    loop {
+        let config: BridgeConfig = read_config(&get_config_path());
+        let url = format!("http://{}:{}/api/mcp/servers", config.host, config.port);
+        let client = build_client_with_timeout(config.proxy.as_deref(), Duration::from_secs(5));
+
+        if let Ok(response) = client.get(&url).send().await {
+            if let Ok(json) = response.json::<serde_json::Value>().await {
+                let servers = json.get("servers").cloned().unwrap_or(json);
+                if let Ok(list) = serde_json::from_value::<Vec<McpServerStatus>>(servers) {
+                    let state = app_handle.state::<McpStatusStreamState>();
+                    if let Ok(mut last_known) = state.last_known.lock() {
+                        for status in &list {
+                            let changed = last_known.get(&status.id).map_or(true, |prev| {
+                                prev.status != status.status || prev.tool_count != status.tool_count
+                            });
+                            if changed {
+                                emit_status(&app_handle, status);
+                                last_known.insert(status.id.clone(), status.clone());
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
@@ -0,0 +1,202 @@
+//! MCP 服务器监督子系统
+//!
+//! MCP 服务器是由 Bridge 服务管理、经 `/api/mcp/servers/*` 暴露的（见
+//! `mcp_status_stream` 开头的说明），桌面端并不直接 spawn 它们的子进程，所以
+//! 这里的“监督”落在桌面端能触达的那一层：定期检查每个 `autoStart` 服务器
+//! 的状态，一旦发现它不是 running（Bridge 侧崩溃、被外部杀掉、Bridge 启动时
+//! 还没来得及拉起它……）就按该服务器自己的 `maxRestarts`/`backoffCeilingSecs`
+//! 退避策略调用既有的启动/重启端点；状态正常时，顺带拿一次
+//! `/api/mcp/servers/{id}/tools` 当健康探测，确认它不仅“活着”还能正常应答，
+//! 同时刷新 `tool_count`。退避在服务器连续健康一段时间后清零，重启次数用尽后
+//! 通过 `office://log` 推一条说明并停止再尝试，不会无限重启刷屏；服务器状态里的
+//! `last_error` 由 Bridge 提供，这里不写入。
+
+use crate::config::{build_client, build_client_with_timeout, get_config_path, get_mcp_servers_path, read_config, BridgeConfig, McpServerConfig, McpServersConfig};
+use crate::mcp_status_stream::emit_mcp_log;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicI64, AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::AppHandle;
+
+/// 监督循环的检查节奏；每个服务器自己的 `health_check_interval_secs` 决定
+/// 这个节奏里哪些 tick 真正发起检查，而不是各起一个独立任务
+const TICK_INTERVAL: Duration = Duration::from_secs(5);
+/// 连续健康超过这个时长才把退避和重启计数清零，避免刚重启又立刻崩溃时提前清零
+const HEALTHY_RESET_AFTER_SECS: i64 = 120;
+
+/// 单个 `autoStart` 服务器的监督状态
+#[derive(Default)]
+struct SupervisedServer {
+    last_checked_at: AtomicI64,
+    healthy_since: AtomicI64,
+    restart_count: AtomicU32,
+    backoff_secs: Mutex<u64>,
+    gave_up: std::sync::atomic::AtomicBool,
+}
+
+impl SupervisedServer {
+    fn new() -> Self {
+        Self {
+            backoff_secs: Mutex::new(1),
+            ..Default::default()
+        }
+    }
+}
+
+/// 监督循环里每个服务器的运行态，跨 tick 保留
+#[derive(Default)]
+pub struct McpSupervisorState {
+    servers: Mutex<HashMap<String, SupervisedServer>>,
+}
+
+fn now_ts() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// 拉一次 `/api/mcp/servers`，按 id 找到对应服务器的 `status` 字段
+async fn fetch_status(config: &BridgeConfig, id: &str) -> Option<String> {
+    let url = format!("http://{}:{}/api/mcp/servers", config.host, config.port);
+    let client = build_client_with_timeout(config.proxy.as_deref(), Duration::from_secs(5));
+    let response = client.get(&url).send().await.ok()?;
+    let json: serde_json::Value = response.json().await.ok()?;
+    let servers = json.get("servers").cloned().unwrap_or(json);
+    servers.as_array()?.iter().find_map(|s| {
+        if s.get("id")?.as_str()? == id {
+            s.get("status")?.as_str().map(|s| s.to_string())
+        } else {
+            None
+        }
+    })
+}
+
+/// 健康探测：跑一次 `tools/list` 往返，顺带当作“进程不仅活着还能正常应答”的信号
+async fn probe_tools_count(config: &BridgeConfig, id: &str) -> Option<usize> {
+    let url = format!("http://{}:{}/api/mcp/servers/{}/tools", config.host, config.port, id);
+    let client = build_client_with_timeout(config.proxy.as_deref(), Duration::from_secs(5));
+    let response = client.get(&url).send().await.ok()?;
+    if !response.status().is_success() {
+        return None;
+    }
+    let json: serde_json::Value = response.json().await.ok()?;
+    json.get("tools")?.as_array().map(|tools| tools.len())
+}
+
+/// 调用既有的启动/重启端点把服务器拉起来；不知道它当前是否在跑，统一走 restart
+/// 即可——Bridge 侧的 restart 端点对已停止的服务器等价于 start
+async fn trigger_restart(config: &BridgeConfig, id: &str) -> Result<(), String> {
+    let url = format!("http://{}:{}/api/mcp/servers/{}/restart", config.host, config.port, id);
+    let client = build_client(config.proxy.as_deref());
+    match client.post(&url).send().await {
+        Ok(response) if response.status().is_success() => Ok(()),
+        Ok(response) => Err(format!("重启失败: HTTP {}", response.status())),
+        Err(e) => Err(format!("重启请求失败: {}", e)),
+    }
+}
+
+/// 在应用启动时调用一次，开启后台监督任务
+pub fn spawn_mcp_supervisor(app_handle: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(TICK_INTERVAL).await;
+            tick(&app_handle).await;
+        }
+    });
+}
+
+async fn tick(app_handle: &AppHandle) {
+    let servers_config: McpServersConfig = read_config(&get_mcp_servers_path());
+    let bridge_config: BridgeConfig = read_config(&get_config_path());
+
+    for server in servers_config.servers.iter().filter(|s| s.enabled && s.auto_start) {
+        check_one(app_handle, &bridge_config, server).await;
+    }
+}
+
+async fn check_one(app_handle: &AppHandle, bridge_config: &BridgeConfig, server: &McpServerConfig) {
+    use tauri::Manager;
+    let state = app_handle.state::<McpSupervisorState>();
+
+    let health_check_interval = server.health_check_interval_secs.unwrap_or(30) as i64;
+    let max_restarts = server.max_restarts.unwrap_or(10);
+    let backoff_ceiling = server.backoff_ceiling_secs.unwrap_or(60);
+
+    {
+        let mut servers = match state.servers.lock() {
+            Ok(guard) => guard,
+            Err(_) => return,
+        };
+        let supervised = servers.entry(server.id.clone()).or_insert_with(SupervisedServer::new);
+        if supervised.gave_up.load(Ordering::SeqCst) {
+            return;
+        }
+        let last_checked = supervised.last_checked_at.load(Ordering::SeqCst);
+        if now_ts() - last_checked < health_check_interval {
+            return;
+        }
+        supervised.last_checked_at.store(now_ts(), Ordering::SeqCst);
+    }
+
+    let status = fetch_status(bridge_config, &server.id).await;
+    let healthy = match status.as_deref() {
+        Some("running") => probe_tools_count(bridge_config, &server.id).await.is_some(),
+        _ => false,
+    };
+
+    let servers = match state.servers.lock() {
+        Ok(guard) => guard,
+        Err(_) => return,
+    };
+    let Some(supervised) = servers.get(&server.id) else { return };
+
+    if healthy {
+        if supervised.healthy_since.load(Ordering::SeqCst) == 0 {
+            supervised.healthy_since.store(now_ts(), Ordering::SeqCst);
+        }
+        let healthy_since = supervised.healthy_since.load(Ordering::SeqCst);
+        if now_ts() - healthy_since >= HEALTHY_RESET_AFTER_SECS {
+            supervised.restart_count.store(0, Ordering::SeqCst);
+            if let Ok(mut backoff) = supervised.backoff_secs.lock() {
+                *backoff = 1;
+            }
+        }
+        return;
+    }
+
+    supervised.healthy_since.store(0, Ordering::SeqCst);
+    let restart_count = supervised.restart_count.fetch_add(1, Ordering::SeqCst) + 1;
+    if restart_count > max_restarts {
+        supervised.gave_up.store(true, Ordering::SeqCst);
+        let message = "已达到最大重启次数，停止自动重启";
+        emit_mcp_log(app_handle, &server.id, message);
+        return;
+    }
+
+    let backoff = {
+        let mut guard = match supervised.backoff_secs.lock() {
+            Ok(guard) => guard,
+            Err(_) => return,
+        };
+        let current = (*guard).min(backoff_ceiling);
+        *guard = (current * 2).min(backoff_ceiling);
+        current
+    };
+    drop(servers);
+
+    let reason = match status {
+        Some(s) => format!("MCP 服务器状态异常（{}），{} 秒后尝试自动重启", s, backoff),
+        None => format!("MCP 服务器无响应，{} 秒后尝试自动重启", backoff),
+    };
+    emit_mcp_log(app_handle, &server.id, &reason);
+
+    tokio::time::sleep(Duration::from_secs(backoff)).await;
+
+    if let Err(e) = trigger_restart(bridge_config, &server.id).await {
+        emit_mcp_log(app_handle, &server.id, &format!("自动重启失败: {}", e));
+    } else {
+        emit_mcp_log(app_handle, &server.id, "已自动重启");
+    }
+}
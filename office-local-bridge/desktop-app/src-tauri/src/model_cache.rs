@@ -0,0 +1,114 @@
+//! 模型列表缓存
+//!
+//! `get_provider_models` 以前每次都原样转发 Bridge 返回的 `serde_json::Value`，
+//! 设置面板每次打开都要重新请求、重新解析，`ModelInfo` 上的
+//! `supports_vision`/`supports_tools`/`supports_streaming`/`context_window`
+//! 这些能力标记也没有被用来做任何筛选。这里把模型列表按 `provider_id`
+//! 持久化到一份带 TTL 的缓存文件里，并提供 `query_models` 支持按能力
+//! 筛选；`validate_provider` 拿到的 `models` 也会直接拿来预热缓存。
+
+use crate::commands::ModelInfo;
+use crate::config::{get_config_dir, read_config, write_config};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 缓存条目多久算过期，超过这个时间 `query_models` 会重新向 Bridge 请求
+const CACHE_TTL_SECS: i64 = 3600;
+
+/// 获取模型缓存文件路径
+pub fn get_model_cache_path() -> PathBuf {
+    get_config_dir().join("model-cache.json")
+}
+
+/// 单个供应商的缓存条目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelCacheEntry {
+    pub models: Vec<ModelInfo>,
+    pub fetched_at: i64,
+}
+
+/// 按 `provider_id` 索引的模型缓存
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelCacheStore {
+    pub version: i32,
+    pub entries: HashMap<String, ModelCacheEntry>,
+}
+
+impl Default for ModelCacheStore {
+    fn default() -> Self {
+        Self {
+            version: 1,
+            entries: HashMap::new(),
+        }
+    }
+}
+
+fn now_ts() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// 按能力筛选模型列表的条件，字段留空表示不筛选该条件
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelFilter {
+    pub supports_vision: Option<bool>,
+    pub supports_tools: Option<bool>,
+    pub supports_streaming: Option<bool>,
+    pub min_context_window: Option<i64>,
+    /// 为 `true` 时跳过缓存，强制向 Bridge 重新拉取
+    pub force_refresh: Option<bool>,
+}
+
+/// 判断某个模型是否满足筛选条件
+pub fn matches_filter(model: &ModelInfo, filter: &ModelFilter) -> bool {
+    if let Some(want) = filter.supports_vision {
+        if model.supports_vision.unwrap_or(false) != want {
+            return false;
+        }
+    }
+    if let Some(want) = filter.supports_tools {
+        if model.supports_tools.unwrap_or(false) != want {
+            return false;
+        }
+    }
+    if let Some(want) = filter.supports_streaming {
+        if model.supports_streaming.unwrap_or(false) != want {
+            return false;
+        }
+    }
+    if let Some(min_window) = filter.min_context_window {
+        if model.context_window.unwrap_or(0) < min_window {
+            return false;
+        }
+    }
+    true
+}
+
+/// 读取某个供应商未过期的缓存，没有缓存或已过期则返回 `None`
+pub fn load_cached_models(provider_id: &str) -> Option<Vec<ModelInfo>> {
+    let store: ModelCacheStore = read_config(&get_model_cache_path());
+    let entry = store.entries.get(provider_id)?;
+    if now_ts() - entry.fetched_at > CACHE_TTL_SECS {
+        return None;
+    }
+    Some(entry.models.clone())
+}
+
+/// 写入/覆盖某个供应商的缓存，供 `validate_provider`、`query_models` 预热或刷新使用
+pub fn warm_cache(provider_id: &str, models: Vec<ModelInfo>) {
+    let mut store: ModelCacheStore = read_config(&get_model_cache_path());
+    store.entries.insert(
+        provider_id.to_string(),
+        ModelCacheEntry {
+            models,
+            fetched_at: now_ts(),
+        },
+    );
+    let _ = write_config(&get_model_cache_path(), &store);
+}
@@ -0,0 +1,150 @@
+//! 供应商注册表
+//!
+//! `ValidateProviderRequest` 曾经把 `azure_endpoint`/`azure_deployment`/
+//! `azure_api_version` 硬编码成顶层可选字段，每加一种新供应商形态都要改
+//! 结构体和 `validate_provider` 里手搭的 `serde_json::json!` 请求体。这里
+//! 换成按 `type` 打标签的枚举，每个 variant 只携带自己需要的字段，并通过
+//! `ProviderValidate` trait 构建请求体，新增供应商就只是一个新 variant
+//! + 一次 impl。
+
+use crate::config::AIProviderType;
+use serde::{Deserialize, Serialize};
+
+/// 所有供应商共同实现的行为：如何把自己的配置组装成验证请求体
+pub trait ProviderValidate {
+    fn build_validate_body(&self) -> serde_json::Value;
+
+    /// 供应商类型标签，主要用于日志和诊断
+    fn provider_type(&self) -> AIProviderType;
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenAiValidateConfig {
+    pub api_key: String,
+    pub base_url: Option<String>,
+}
+
+impl ProviderValidate for OpenAiValidateConfig {
+    fn build_validate_body(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "openai",
+            "apiKey": self.api_key,
+            "baseUrl": self.base_url,
+        })
+    }
+
+    fn provider_type(&self) -> AIProviderType {
+        AIProviderType::OpenAI
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnthropicValidateConfig {
+    pub api_key: String,
+    pub base_url: Option<String>,
+}
+
+impl ProviderValidate for AnthropicValidateConfig {
+    fn build_validate_body(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "anthropic",
+            "apiKey": self.api_key,
+            "baseUrl": self.base_url,
+        })
+    }
+
+    fn provider_type(&self) -> AIProviderType {
+        AIProviderType::Anthropic
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OllamaValidateConfig {
+    pub base_url: Option<String>,
+}
+
+impl ProviderValidate for OllamaValidateConfig {
+    fn build_validate_body(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "ollama",
+            "baseUrl": self.base_url,
+        })
+    }
+
+    fn provider_type(&self) -> AIProviderType {
+        AIProviderType::Ollama
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AzureValidateConfig {
+    pub api_key: String,
+    pub azure_endpoint: String,
+    pub azure_deployment: String,
+    pub azure_api_version: Option<String>,
+}
+
+impl ProviderValidate for AzureValidateConfig {
+    fn build_validate_body(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "azure",
+            "apiKey": self.api_key,
+            "azureEndpoint": self.azure_endpoint,
+            "azureDeployment": self.azure_deployment,
+            "azureApiVersion": self.azure_api_version,
+        })
+    }
+
+    fn provider_type(&self) -> AIProviderType {
+        AIProviderType::Azure
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomValidateConfig {
+    pub api_key: String,
+    pub base_url: Option<String>,
+}
+
+impl ProviderValidate for CustomValidateConfig {
+    fn build_validate_body(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "custom",
+            "apiKey": self.api_key,
+            "baseUrl": self.base_url,
+        })
+    }
+
+    fn provider_type(&self) -> AIProviderType {
+        AIProviderType::Custom
+    }
+}
+
+/// 按 `type` 打标签的验证请求，取代原先携带一堆 Azure 专属可选字段的
+/// `ValidateProviderRequest`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ValidateProviderRequest {
+    OpenAI(OpenAiValidateConfig),
+    Anthropic(AnthropicValidateConfig),
+    Ollama(OllamaValidateConfig),
+    Azure(AzureValidateConfig),
+    Custom(CustomValidateConfig),
+}
+
+impl ValidateProviderRequest {
+    pub fn build_validate_body(&self) -> serde_json::Value {
+        match self {
+            ValidateProviderRequest::OpenAI(c) => c.build_validate_body(),
+            ValidateProviderRequest::Anthropic(c) => c.build_validate_body(),
+            ValidateProviderRequest::Ollama(c) => c.build_validate_body(),
+            ValidateProviderRequest::Azure(c) => c.build_validate_body(),
+            ValidateProviderRequest::Custom(c) => c.build_validate_body(),
+        }
+    }
+}
@@ -0,0 +1,44 @@
+//! 提供商 API Key 的系统密钥库存取
+//!
+//! `AIProviderConfig.api_key` 以前是明文写进 `providers.json` 的，任何能读到
+//! 这个文件的人都能拿到所有供应商的密钥。这里改成文件里只留空字符串占位，
+//! 真正的 key 存进平台密钥库——macOS Keychain、Windows Credential Manager、
+//! Linux Secret Service，用法和 Spacedrive 迁移到 Tauri 2 时一样，统一靠
+//! `keyring` crate 屏蔽平台差异。账户名固定为 `office-local-bridge:<providerId>`，
+//! 迁移、读写的编排逻辑在 `config::read_providers_config`/`write_providers_config`。
+
+use keyring::Entry;
+
+const SERVICE_NAME: &str = "office-local-bridge";
+
+fn account_name(provider_id: &str) -> String {
+    format!("office-local-bridge:{}", provider_id)
+}
+
+/// 把某个供应商的 API Key 写入系统密钥库；传空字符串等价于删除该条目
+pub fn store_api_key(provider_id: &str, api_key: &str) -> Result<(), String> {
+    if api_key.is_empty() {
+        return delete_api_key(provider_id);
+    }
+    let entry = Entry::new(SERVICE_NAME, &account_name(provider_id)).map_err(|e| e.to_string())?;
+    entry
+        .set_password(api_key)
+        .map_err(|e| format!("写入密钥库失败: {}", e))
+}
+
+/// 读取某个供应商的 API Key；密钥库里没有对应条目时返回空字符串，不当作错误
+pub fn load_api_key(provider_id: &str) -> String {
+    match Entry::new(SERVICE_NAME, &account_name(provider_id)) {
+        Ok(entry) => entry.get_password().unwrap_or_default(),
+        Err(_) => String::new(),
+    }
+}
+
+/// 从密钥库中删除某个供应商的 API Key 条目；条目本就不存在也算成功
+pub fn delete_api_key(provider_id: &str) -> Result<(), String> {
+    let entry = Entry::new(SERVICE_NAME, &account_name(provider_id)).map_err(|e| e.to_string())?;
+    match entry.delete_password() {
+        Ok(_) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(format!("删除密钥库条目失败: {}", e)),
+    }
+}
@@ -0,0 +1,125 @@
+//! 应用自更新子系统
+//!
+//! 用 `tauri-plugin-updater` 检查/下载已签名的更新清单，校验签名后安装并
+//! 重启应用。更新会替换掉当前可执行文件，所以安装前要先按
+//! `stop_bridge_service` 同一套逻辑停掉桥接子进程，避免留下指向旧文件的
+//! 僵尸句柄。本模块不会在应用重启后重新拉起桥接服务：新进程里
+//! `spawn_supervisor` 只在 `start_bridge_service`/`restart_bridge_service`
+//! 启动过桥接服务之后才会监督并自动重启它。下载进度通过 `office://update-progress`
+//! 推送给前端渲染进度条。
+
+use crate::bridge_supervisor::BridgeProcessState;
+use crate::commands::ApiResponse;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_updater::UpdaterExt;
+
+/// 检查更新后返回给前端的摘要信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateCheckResult {
+    pub available: bool,
+    pub current_version: String,
+    pub latest_version: Option<String>,
+    pub notes: Option<String>,
+}
+
+/// 下载/安装进度事件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateProgressEvent {
+    pub downloaded: u64,
+    pub total: Option<u64>,
+    /// "downloading" | "installed" | "relaunching"
+    pub status: String,
+}
+
+/// 把一条进度事件推给所有窗口，payload 只序列化一次
+fn emit_progress(app: &AppHandle, downloaded: u64, total: Option<u64>, status: &str) {
+    let event = UpdateProgressEvent {
+        downloaded,
+        total,
+        status: status.to_string(),
+    };
+    let _ = app.emit_filter("office://update-progress", &event, |_| true);
+}
+
+/// 检查是否有新版本可用，不下载
+#[tauri::command]
+pub async fn check_for_update(app: AppHandle) -> ApiResponse<UpdateCheckResult> {
+    let current_version = app.package_info().version.to_string();
+
+    let updater = match app.updater() {
+        Ok(updater) => updater,
+        Err(e) => return ApiResponse::error(&format!("初始化更新器失败: {}", e)),
+    };
+
+    match updater.check().await {
+        Ok(Some(update)) => {
+            let result = UpdateCheckResult {
+                available: true,
+                current_version,
+                latest_version: Some(update.version.clone()),
+                notes: update.body.clone(),
+            };
+            // 托盘菜单触发的检查没有直接的返回值通道，靠这条事件通知前端弹窗
+            let _ = app.emit_filter("office://update-available", &result, |_| true);
+            ApiResponse::success(result)
+        }
+        Ok(None) => ApiResponse::success(UpdateCheckResult {
+            available: false,
+            current_version,
+            latest_version: None,
+            notes: None,
+        }),
+        Err(e) => ApiResponse::error(&format!("检查更新失败: {}", e)),
+    }
+}
+
+/// 下载并安装更新：停桥接服务 -> 校验签名并下载 -> 安装 -> 重启应用
+#[tauri::command]
+pub async fn install_update(
+    app: AppHandle,
+    bridge_state: tauri::State<'_, BridgeProcessState>,
+) -> Result<ApiResponse<bool>, String> {
+    let updater = app.updater().map_err(|e| format!("初始化更新器失败: {}", e))?;
+
+    let update = match updater.check().await {
+        Ok(Some(update)) => update,
+        Ok(None) => return Ok(ApiResponse::error("当前已是最新版本")),
+        Err(e) => return Ok(ApiResponse::error(&format!("检查更新失败: {}", e))),
+    };
+
+    // 安装包会覆盖当前可执行文件，先停掉桥接子进程再动手
+    let _ = crate::commands::stop_bridge_service(bridge_state, app.clone()).await;
+
+    emit_progress(&app, 0, None, "downloading");
+
+    // 下载进度回调和完成回调是两个独立的闭包，各自 move 捕获会各拿一份副本，
+    // 完成回调看到的永远是创建时的初始值 —— 用 Arc<AtomicU64> 让两边共享同一个计数器
+    let downloaded = Arc::new(AtomicU64::new(0));
+    let progress_downloaded = downloaded.clone();
+    let progress_app = app.clone();
+    let finished_downloaded = downloaded.clone();
+    let finished_app = app.clone();
+
+    let install_result = update
+        .download_and_install(
+            move |chunk_len, total| {
+                let downloaded = progress_downloaded.fetch_add(chunk_len as u64, Ordering::SeqCst) + chunk_len as u64;
+                emit_progress(&progress_app, downloaded, total, "downloading");
+            },
+            move || emit_progress(&finished_app, finished_downloaded.load(Ordering::SeqCst), None, "installed"),
+        )
+        .await;
+
+    match install_result {
+        Ok(_) => {
+            emit_progress(&app, downloaded.load(Ordering::SeqCst), None, "relaunching");
+            app.restart();
+        }
+        Err(e) => Ok(ApiResponse::error(&format!("安装更新失败: {}", e))),
+    }
+}
@@ -0,0 +1,71 @@
+//! 增量 UTF-8 解码缓冲
+//!
+//! 网络流是按字节分片到达的，一个多字节 UTF-8 序列完全可能被硬生生切在
+//! 两个分片的边界上。对每个分片各自调用 `String::from_utf8_lossy` 会把
+//! 被切断的序列当成非法字节，替换成 `U+FFFD`，而不是等下一个分片到达后
+//! 拼起来再解码——这里用 `Utf8ChunkBuffer` 补上这一步：只对外吐出已经能
+//! 安全解码的前缀，不完整的尾部字节留在内部缓冲里，等下次 `push` 再拼接。
+
+/// 逐块喂入原始字节，只返回当前已可安全解码的文本前缀
+#[derive(Debug, Default)]
+pub struct Utf8ChunkBuffer {
+    pending: Vec<u8>,
+}
+
+impl Utf8ChunkBuffer {
+    pub fn push(&mut self, bytes: &[u8]) -> String {
+        self.pending.extend_from_slice(bytes);
+
+        let mut out = String::new();
+        loop {
+            match std::str::from_utf8(&self.pending) {
+                Ok(text) => {
+                    out.push_str(text);
+                    self.pending.clear();
+                    break;
+                }
+                Err(e) => {
+                    let valid_up_to = e.valid_up_to();
+                    out.push_str(std::str::from_utf8(&self.pending[..valid_up_to]).unwrap_or_default());
+                    match e.error_len() {
+                        // 真正非法的字节（不是序列还没读全），跳过去避免卡死，
+                        // 用替换字符顶上，和 `from_utf8_lossy` 的行为保持一致
+                        Some(invalid_len) => {
+                            out.push('\u{FFFD}');
+                            self.pending.drain(..valid_up_to + invalid_len);
+                        }
+                        // 序列还没读全，留着尾部字节等下次追加新数据
+                        None => {
+                            self.pending.drain(..valid_up_to);
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_multibyte_sequence_split_across_two_pushes() {
+        let mut buf = Utf8ChunkBuffer::default();
+        let bytes = "你好".as_bytes();
+        // 把"你"切成两半喂进去，第一次不应该吐出替换字符
+        let mut first = String::new();
+        first.push_str(&buf.push(&bytes[..1]));
+        first.push_str(&buf.push(&bytes[1..3]));
+        first.push_str(&buf.push(&bytes[3..]));
+        assert_eq!(first, "你好");
+    }
+
+    #[test]
+    fn decodes_whole_chunk_immediately_when_complete() {
+        let mut buf = Utf8ChunkBuffer::default();
+        assert_eq!(buf.push("hello".as_bytes()), "hello");
+    }
+}